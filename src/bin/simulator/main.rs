@@ -0,0 +1,97 @@
+//! Walks through signing the transactions of a scenario file, answering every
+//! prompt on the terminal:
+//!
+//! ```sh
+//! cargo run --bin simulator -- scenarios/example.json
+//! ```
+
+mod scenario;
+mod terminal_user;
+
+use std::sync::Arc;
+
+use itertools::Itertools;
+use rust_factors::prelude::*;
+use scenario::Scenario;
+use terminal_user::TerminalSigningUser;
+
+fn describe_outcome(outcome: SignaturesOutcome) {
+    println!("\nSigning done");
+    for transaction in outcome.successful_transactions {
+        println!(
+            "  Successful {} - {} signatures",
+            transaction.intent_hash,
+            transaction.signatures.len()
+        );
+    }
+    for transaction in outcome.failed_transactions {
+        println!(
+            "  Failed {} - entities which failed auth: {}",
+            transaction.intent_hash,
+            transaction
+                .entities_which_failed_auth
+                .iter()
+                .map(|address| address.name.as_str())
+                .join(", ")
+        );
+    }
+    for (factor_source_id, reason) in outcome.skipped_factor_sources {
+        println!(
+            "  Skipped {:?} {} - {:?}",
+            factor_source_id.kind, factor_source_id.id, reason
+        );
+    }
+    for (factor_source_id, reason) in outcome.neglected_factor_sources {
+        println!(
+            "  Neglected {:?} {} - {:?}",
+            factor_source_id.kind, factor_source_id.id, reason
+        );
+    }
+}
+
+async fn simulate(path: &str) -> Result<(), String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read '{path}': {e}"))?;
+    let (factor_sources, transactions, signer) = Scenario::from_json(&json)?.build()?;
+
+    println!("Transactions to sign:");
+    for transaction in transactions.iter() {
+        println!(
+            "  {} - entities: {}",
+            transaction.intent_hash,
+            transaction
+                .entities_requiring_auth
+                .iter()
+                .map(|entity| entity.address.name.as_str())
+                .join(", ")
+        );
+    }
+
+    let context = SignaturesBuilderLevel0::new_with_options(
+        SigningUser::host(TerminalSigningUser::stdio()),
+        factor_sources,
+        transactions.into_iter().collect(),
+        SigningOptions {
+            signer: Arc::new(signer),
+            ..Default::default()
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    match context.sign().await.map_err(|e| e.to_string())? {
+        SigningOutcome::Signed(outcome) => describe_outcome(outcome),
+        SigningOutcome::Cancelled => println!("\nSigning cancelled"),
+    }
+    Ok(())
+}
+
+#[actix_rt::main]
+async fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("Usage: simulator <scenario.json>");
+        std::process::exit(2);
+    };
+    if let Err(error) = simulate(&path).await {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,178 @@
+use rust_factors::prelude::*;
+use serde::Deserialize;
+
+/// Entities, factor sources and transactions to sign, loaded from a JSON
+/// file, where entities and factor sources are referred to by name, e.g.:
+///
+/// ```json
+/// {
+///   "factor_sources": [
+///     { "name": "phone", "kind": "Device" },
+///     { "name": "ledger", "kind": "Ledger", "failures": ["WrongPin"] }
+///   ],
+///   "entities": [
+///     { "name": "Alice", "unsecurified": "phone" },
+///     {
+///       "name": "Bob",
+///       "securified": {
+///         "threshold_factors": ["phone"],
+///         "threshold": 1,
+///         "override_factors": ["ledger"]
+///       }
+///     }
+///   ],
+///   "transactions": [["Alice", "Bob"], ["Bob"]]
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Scenario {
+    factor_sources: Vec<ScenarioFactorSource>,
+    entities: Vec<ScenarioEntity>,
+    /// Names of the entities requiring auth, per transaction.
+    transactions: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScenarioFactorSource {
+    name: String,
+    kind: FactorSourceKind,
+    /// Failures to emulate when signing, in order, see
+    /// `SimulatedFactorSourceSigner`.
+    #[serde(default)]
+    failures: Vec<CommonError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScenarioEntity {
+    name: String,
+    #[serde(flatten)]
+    security: ScenarioSecurity,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ScenarioSecurity {
+    /// Name of the single factor source controlling the entity.
+    Unsecurified(String),
+    Securified(ScenarioMatrix),
+}
+
+#[derive(Debug, Deserialize)]
+struct ScenarioMatrix {
+    #[serde(default)]
+    threshold_factors: Vec<String>,
+    #[serde(default)]
+    threshold: u8,
+    #[serde(default)]
+    override_factors: Vec<String>,
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid scenario: {e}"))
+    }
+
+    /// Creates the factor sources and the transactions, with entities
+    /// controlled by those factor sources, and the signer emulating the
+    /// failures of the factor sources, fails if a name is unknown.
+    pub fn build(
+        self,
+    ) -> Result<
+        (
+            IndexSet<FactorSource>,
+            Vec<TransactionIntent>,
+            SimulatedFactorSourceSigner,
+        ),
+        String,
+    > {
+        let mut signer = SimulatedFactorSourceSigner::default();
+        let mut factor_sources = IndexMap::<String, FactorSource>::new();
+        for f in self.factor_sources {
+            let factor_source = FactorSource::new(f.kind);
+            signer = signer.with_failures(factor_source.id, f.failures);
+            factor_sources.insert(f.name, factor_source);
+        }
+
+        let instance = |index: usize, name: &String| {
+            factor_sources
+                .get(name)
+                .map(|f| FactorInstance::new(index as u32, f.id))
+                .ok_or_else(|| format!("Unknown factor source '{name}'"))
+        };
+        let instances = |index: usize, names: &[String]| {
+            names
+                .iter()
+                .map(|name| instance(index, name))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let mut entities = IndexMap::<String, Entity>::new();
+        for (index, entity) in self.entities.into_iter().enumerate() {
+            let security_state = match entity.security {
+                ScenarioSecurity::Unsecurified(name) => {
+                    EntitySecurityState::Unsecured(instance(index, &name)?)
+                }
+                ScenarioSecurity::Securified(matrix) => {
+                    EntitySecurityState::Securified(MatrixOfFactorInstances {
+                        threshold_factors: instances(index, &matrix.threshold_factors)?,
+                        threshold: matrix.threshold,
+                        override_factors: instances(index, &matrix.override_factors)?,
+                    })
+                }
+            };
+            entities.insert(
+                entity.name.clone(),
+                Entity::new(entity.name, security_state),
+            );
+        }
+
+        let transactions = self
+            .transactions
+            .iter()
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| {
+                        entities
+                            .get(name)
+                            .cloned()
+                            .ok_or_else(|| format!("Unknown entity '{name}'"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(TransactionIntent::new)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((factor_sources.into_values().collect(), transactions, signer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_scenario_builds() {
+        let scenario =
+            Scenario::from_json(include_str!("../../../scenarios/example.json")).unwrap();
+        let (factor_sources, transactions, _) = scenario.build().unwrap();
+        assert_eq!(factor_sources.len(), 5);
+        assert_eq!(transactions.len(), 3);
+    }
+
+    #[test]
+    fn unknown_factor_source_is_error() {
+        let scenario = Scenario::from_json(
+            r#"{
+                "factor_sources": [],
+                "entities": [{ "name": "Alice", "unsecurified": "phone" }],
+                "transactions": [["Alice"]]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            scenario.build().err(),
+            Some("Unknown factor source 'phone'".to_owned())
+        );
+    }
+}
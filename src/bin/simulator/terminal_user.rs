@@ -0,0 +1,275 @@
+use std::io::{BufRead, Write};
+use std::sync::Mutex;
+
+use itertools::Itertools;
+use rust_factors::prelude::*;
+
+/// A user answering every prompt on a terminal, e.g. used by the `simulator`
+/// binary to walk through signing flows without a wallet app.
+///
+/// Reading the answer blocks the thread, which is fine for a single user
+/// at a terminal. The user cancels if `input` reaches end of file.
+pub struct TerminalSigningUser {
+    input: Mutex<Box<dyn BufRead + Send>>,
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl TerminalSigningUser {
+    pub fn new(input: impl BufRead + Send + 'static, output: impl Write + Send + 'static) -> Self {
+        Self {
+            input: Mutex::new(Box::new(input)),
+            output: Mutex::new(Box::new(output)),
+        }
+    }
+
+    /// Prompts on stdout and reads answers from stdin.
+    pub fn stdio() -> Self {
+        Self::new(std::io::BufReader::new(std::io::stdin()), std::io::stdout())
+    }
+
+    fn write(&self, text: impl AsRef<str>) {
+        let mut output = self.output.lock().unwrap();
+        // A closed terminal is noticed when reading the answer.
+        let _ = output.write_all(text.as_ref().as_bytes());
+        let _ = output.flush();
+    }
+
+    /// Describes the factor source and the transactions which would be
+    /// invalid if it was skipped.
+    fn describe(
+        &self,
+        factor_source: &FactorSource,
+        invalid_tx_if_skipped: &IndexSet<InvalidTransactionIfSkipped>,
+    ) {
+        let mut text = format!(
+            "\nFactor source {:?} {}\n",
+            factor_source.kind(),
+            factor_source.id.id
+        );
+        if invalid_tx_if_skipped.is_empty() {
+            text.push_str("  Can be skipped, no transaction would be invalid.\n");
+        } else {
+            text.push_str("  If skipped, these transactions would be invalid:\n");
+            for invalid in invalid_tx_if_skipped {
+                text.push_str(&format!(
+                    "    {} - entities: {}\n",
+                    invalid.intent_hash,
+                    invalid
+                        .entities_which_would_fail_auth
+                        .iter()
+                        .map(|address| address.name.as_str())
+                        .join(", ")
+                ));
+            }
+        }
+        self.write(text);
+    }
+
+    /// Describes the progress of every transaction and entity.
+    fn describe_progress(&self, context: &SigningPromptContext) {
+        let mut text = format!(
+            "\nFactor source {} of {} - transactions fulfilled: {}, pending: {}, invalid: {}\n",
+            context.position + 1,
+            context.number_of_factor_sources,
+            context.number_of_transactions_with_status(TransactionSigningStatus::Fulfilled),
+            context.number_of_transactions_with_status(TransactionSigningStatus::Pending),
+            context.number_of_transactions_with_status(TransactionSigningStatus::Invalid),
+        );
+        for transaction in context.transactions.iter() {
+            text.push_str(&format!(
+                "  {} - {:?}\n",
+                transaction.intent_hash, transaction.status
+            ));
+            for entity in transaction.entities.iter() {
+                text.push_str(&format!(
+                    "    {}: signed by {}, {} factor sources remaining\n",
+                    entity.address.name, entity.signed, entity.remaining
+                ));
+            }
+        }
+        self.write(text);
+    }
+
+    /// Asks `question` until the user answers with one of the `choices`
+    /// (case insensitive), returns `None` on end of input.
+    fn choose<T: Clone>(&self, question: &str, choices: &[(&str, T)]) -> Option<T> {
+        let mut input = self.input.lock().unwrap();
+        loop {
+            self.write(question);
+            let mut line = String::new();
+            match input.read_line(&mut line) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => {}
+            }
+            let answer = line.trim().to_lowercase();
+            if let Some((_, choice)) = choices.iter().find(|(key, _)| *key == answer) {
+                return Some(choice.clone());
+            }
+            self.write(format!("Unknown answer '{}'\n", line.trim()));
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IsSigningUser for TerminalSigningUser {
+    async fn sign_or_skip(
+        &self,
+        factor_source: &FactorSource,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+    ) -> SigningUserInput {
+        self.describe(factor_source, &invalid_tx_if_skipped);
+        self.choose(
+            "[s]ign, s[k]ip, skip [a]ll of kind or [c]ancel? ",
+            &[
+                ("s", SigningUserInput::Sign),
+                ("k", SigningUserInput::Skip),
+                ("a", SigningUserInput::SkipAllOfKind),
+                ("c", SigningUserInput::Cancel),
+            ],
+        )
+        .unwrap_or(SigningUserInput::Cancel)
+    }
+
+    async fn sign_or_skip_in_context(
+        &self,
+        factor_source: &FactorSource,
+        context: SigningPromptContext,
+    ) -> SigningUserInput {
+        self.describe_progress(&context);
+        self.sign_or_skip(factor_source, context.invalid_tx_if_skipped)
+            .await
+    }
+
+    async fn retry_skip_or_cancel(
+        &self,
+        factor_source: &FactorSource,
+        failure: CommonError,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        can_retry: bool,
+    ) -> SigningRetryInput {
+        self.write(format!("\nSigning failed: {}\n", failure));
+        self.describe(factor_source, &invalid_tx_if_skipped);
+        if can_retry {
+            self.choose(
+                "[r]etry, [s]kip or [c]ancel? ",
+                &[
+                    ("r", SigningRetryInput::Retry),
+                    ("s", SigningRetryInput::Skip),
+                    ("c", SigningRetryInput::Cancel),
+                ],
+            )
+        } else {
+            self.choose(
+                "[s]kip or [c]ancel? ",
+                &[
+                    ("s", SigningRetryInput::Skip),
+                    ("c", SigningRetryInput::Cancel),
+                ],
+            )
+        }
+        .unwrap_or(SigningRetryInput::Cancel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terminal_user(input: &'static str) -> TerminalSigningUser {
+        TerminalSigningUser::new(std::io::Cursor::new(input), std::io::sink())
+    }
+
+    #[actix_rt::test]
+    async fn terminal_user_asks_again_on_unknown_answer() {
+        let user = terminal_user("x\nK\n");
+        assert_eq!(
+            user.sign_or_skip(&FactorSource::fs0(), IndexSet::new())
+                .await,
+            SigningUserInput::Skip
+        );
+    }
+
+    #[actix_rt::test]
+    async fn terminal_user_cancels_on_end_of_input() {
+        let user = terminal_user("");
+        assert_eq!(
+            user.sign_or_skip(&FactorSource::fs0(), IndexSet::new())
+                .await,
+            SigningUserInput::Cancel
+        );
+    }
+
+    #[actix_rt::test]
+    async fn terminal_user_signs() {
+        let context = SignaturesBuilderLevel0::new(
+            SigningUser::host(terminal_user("s\ns\n")),
+            FactorSource::all(),
+            IndexSet::from_iter([TransactionIntent::new([Entity::securified(
+                0,
+                "Frank",
+                |index| {
+                    MatrixOfFactorInstances::threshold_only(
+                        [FactorSourceID::fs1(), FactorSourceID::fs4()]
+                            .map(|id| FactorInstance::new(index, id)),
+                        2,
+                    )
+                },
+            )])]),
+        )
+        .unwrap();
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+    }
+
+    /// Output of a `TerminalSigningUser`, readable after the user is done.
+    #[derive(Clone, Default)]
+    struct SharedOutput(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+    impl SharedOutput {
+        fn written(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+    impl std::io::Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[actix_rt::test]
+    async fn terminal_user_is_told_progress_of_transactions() {
+        let output = SharedOutput::default();
+        let user = TerminalSigningUser::new(std::io::Cursor::new("s\n"), output.clone());
+        let context = SignaturesBuilderLevel0::new(
+            SigningUser::host(terminal_user("")),
+            FactorSource::all(),
+            IndexSet::from_iter([TransactionIntent::new([Entity::unsecurified(
+                0,
+                "Alice",
+                FactorSourceID::fs0(),
+            )])]),
+        )
+        .unwrap();
+        let prompt_context = context.prompt_context(&rust_factors::fs_at(0)).unwrap();
+        assert_eq!(
+            user.sign_or_skip_in_context(&rust_factors::fs_at(0), prompt_context)
+                .await,
+            SigningUserInput::Sign
+        );
+        let written = output.written();
+        assert!(written.contains("Factor source 1 of 1"));
+        assert!(written.contains("Alice: signed by 0, 1 factor sources remaining"));
+    }
+
+    #[actix_rt::test]
+    async fn terminal_user_skips_all_of_kind() {
+        let user = terminal_user("a\n");
+        assert_eq!(
+            user.sign_or_skip(&rust_factors::fs_at(1), IndexSet::new())
+                .await,
+            SigningUserInput::SkipAllOfKind
+        );
+    }
+}
@@ -21,11 +21,26 @@ impl SignaturesBuilderLevel0 {
         all_factor_sources_in_profile: impl IntoIterator<Item = FactorSource>,
         transactions: impl IntoIterator<Item = TransactionIntent>,
     ) -> Self {
-        Self::new(
+        Self::new_test_with_options(
+            user,
+            all_factor_sources_in_profile,
+            transactions,
+            SigningOptions::default(),
+        )
+    }
+    pub fn new_test_with_options(
+        user: TestSigningUser,
+        all_factor_sources_in_profile: impl IntoIterator<Item = FactorSource>,
+        transactions: impl IntoIterator<Item = TransactionIntent>,
+        options: SigningOptions,
+    ) -> Self {
+        Self::new_with_options(
             SigningUser::Test(user),
             all_factor_sources_in_profile.into_iter().collect(),
             transactions.into_iter().collect(),
+            options,
         )
+        .unwrap()
     }
     pub fn test_prudent_with_factors(
         all_factor_sources_in_profile: impl IntoIterator<Item = FactorSource>,
@@ -292,6 +307,7 @@ impl Entity {
 mod tests {
 
     use super::*;
+    use itertools::Itertools;
 
     #[test]
     fn factors_sources() {
@@ -321,7 +337,13 @@ mod tests {
     async fn prudent_user_single_tx_a0() {
         let context =
             SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a0()])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -329,7 +351,13 @@ mod tests {
     async fn prudent_user_single_tx_a0_assert_correct_intent_hash_is_signed() {
         let tx = TransactionIntent::new([Entity::a0()]);
         let context = SignaturesBuilderLevel0::test_prudent([tx.clone()]);
-        let signature = &context.sign().await.all_signatures[0];
+        let signature = &context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures()[0];
         assert_eq!(signature.intent_hash, tx.intent_hash);
     }
 
@@ -338,7 +366,13 @@ mod tests {
         let account = Entity::a0();
         let tx = TransactionIntent::new([account.clone()]);
         let context = SignaturesBuilderLevel0::test_prudent([tx.clone()]);
-        let signature = &context.sign().await.all_signatures[0];
+        let signature = &context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures()[0];
         assert_eq!(signature.owned_factor_instance.owner, account.address);
     }
 
@@ -347,7 +381,13 @@ mod tests {
         let account = Entity::a0();
         let tx = TransactionIntent::new([account.clone()]);
         let context = SignaturesBuilderLevel0::test_prudent([tx.clone()]);
-        let signature = &context.sign().await.all_signatures[0];
+        let signature = &context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures()[0];
 
         assert_eq!(
             &signature.owned_factor_instance.factor_instance,
@@ -363,7 +403,13 @@ mod tests {
     async fn prudent_user_single_tx_a1() {
         let context =
             SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a1()])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -371,7 +417,13 @@ mod tests {
     async fn prudent_user_single_tx_a2() {
         let context =
             SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a2()])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -379,7 +431,13 @@ mod tests {
     async fn prudent_user_single_tx_a3() {
         let context =
             SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a3()])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -387,7 +445,13 @@ mod tests {
     async fn prudent_user_single_tx_a4() {
         let context =
             SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a4()])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 3);
     }
 
@@ -395,7 +459,13 @@ mod tests {
     async fn prudent_user_single_tx_a5() {
         let context =
             SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a5()])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 2);
     }
 
@@ -403,7 +473,13 @@ mod tests {
     async fn prudent_user_single_tx_a6() {
         let context =
             SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a6()])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 5);
     }
 
@@ -412,7 +488,13 @@ mod tests {
         let context = SignaturesBuilderLevel0::test_lazy_sign_minimum([TransactionIntent::new([
             Entity::a0(),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -421,7 +503,13 @@ mod tests {
         let context = SignaturesBuilderLevel0::test_lazy_sign_minimum([TransactionIntent::new([
             Entity::a1(),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -430,7 +518,13 @@ mod tests {
         let context = SignaturesBuilderLevel0::test_lazy_sign_minimum([TransactionIntent::new([
             Entity::a2(),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -439,7 +533,13 @@ mod tests {
         let context = SignaturesBuilderLevel0::test_lazy_sign_minimum([TransactionIntent::new([
             Entity::a3(),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -448,7 +548,13 @@ mod tests {
         let context = SignaturesBuilderLevel0::test_lazy_sign_minimum([TransactionIntent::new([
             Entity::a4(),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 2);
     }
 
@@ -457,7 +563,13 @@ mod tests {
         let context = SignaturesBuilderLevel0::test_lazy_sign_minimum([TransactionIntent::new([
             Entity::a5(),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
     }
 
@@ -466,7 +578,13 @@ mod tests {
         let context = SignaturesBuilderLevel0::test_lazy_sign_minimum([TransactionIntent::new([
             Entity::a6(),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
 
         // 1 signature only, because the first FactorSourceKind to sign with is Ledger, an a Ledger is used as an override factor, so user can skip all subsequent factor sources after having signed with that ledger.
         assert_eq!(signatures.len(), 1);
@@ -492,7 +610,7 @@ mod tests {
                 FactorSource::all(),
                 [transaction],
             );
-            context.sign().await;
+            context.sign().await.unwrap();
         }
     }
 
@@ -502,7 +620,13 @@ mod tests {
         let context = SignaturesBuilderLevel0::test_lazy_sign_minimum([TransactionIntent::new([
             entity.clone(),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
 
         let signature = &signatures[0];
@@ -523,11 +647,17 @@ mod tests {
                 MatrixOfFactorInstances::override_only(
                     FactorSource::all()
                         .into_iter()
-                        .map(|f| FactorInstance::new(idx, f.id.clone())),
+                        .map(|f| FactorInstance::new(idx, f.id)),
                 )
             }),
         ])]);
-        let signatures = context.sign().await.all_signatures;
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
         assert_eq!(signatures.len(), 1);
         let signature = &signatures[0];
         assert_eq!(
@@ -539,4 +669,2892 @@ mod tests {
             FactorSourceKind::Device
         );
     }
+
+    #[actix_rt::test]
+    async fn cancel_on_first_prompt_is_cancelled() {
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::Lazy(Laziness::new(|_, _| SigningUserInput::Cancel)),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+        );
+        let outcome = context.sign().await.unwrap();
+        assert!(outcome.is_cancelled());
+        assert_eq!(outcome.signatures(), None);
+    }
+
+    #[actix_rt::test]
+    async fn cancel_stops_prompting_subsequent_factor_sources() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let prompts_clone = prompts.clone();
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::Lazy(Laziness::new(move |_, _| {
+                // Sign with the first factor source, cancel on the second.
+                if prompts_clone.fetch_add(1, Ordering::SeqCst) == 0 {
+                    SigningUserInput::Sign
+                } else {
+                    SigningUserInput::Cancel
+                }
+            })),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+        );
+        let outcome = context.sign().await.unwrap();
+        assert!(outcome.is_cancelled());
+        assert_eq!(prompts.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_rt::test]
+    async fn outcome_partitions_successful_and_failed_transactions() {
+        let a1 = Entity::a1();
+        let t0 = TransactionIntent::new([Entity::a0()]);
+        let t1 = TransactionIntent::new([a1.clone()]);
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::Lazy(Laziness::new(|factor_source, _| {
+                if factor_source.id == FactorSourceID::fs1() {
+                    SigningUserInput::Skip
+                } else {
+                    SigningUserInput::Sign
+                }
+            })),
+            FactorSource::all(),
+            [t0.clone(), t1.clone()],
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(!outcome.successful());
+
+        assert_eq!(outcome.successful_transactions.len(), 1);
+        assert_eq!(
+            outcome.successful_transactions[0].intent_hash,
+            t0.intent_hash
+        );
+        assert_eq!(outcome.successful_transactions[0].signatures.len(), 1);
+
+        assert_eq!(
+            outcome.failed_transactions,
+            vec![FailedTransaction::new(t1.intent_hash, vec![a1.address])]
+        );
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([(FactorSourceID::fs1(), SkipReason::User)])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn outcome_failed_transaction_lists_only_entities_which_failed_auth() {
+        let a1 = Entity::a1();
+        let tx = TransactionIntent::new([Entity::a0(), a1.clone()]);
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::Lazy(Laziness::new(|factor_source, _| {
+                if factor_source.id == FactorSourceID::fs1() {
+                    SigningUserInput::Skip
+                } else {
+                    SigningUserInput::Sign
+                }
+            })),
+            FactorSource::all(),
+            [tx.clone()],
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful_transactions.is_empty());
+        assert!(outcome.all_signatures().is_empty());
+        assert_eq!(
+            outcome.failed_transactions,
+            vec![FailedTransaction::new(tx.intent_hash, vec![a1.address])]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn outcome_prudent_user_many_transactions_all_successful() {
+        let transactions = Entity::all()
+            .into_iter()
+            .map(|e| TransactionIntent::new([e]))
+            .collect::<IndexSet<_>>();
+        let context = SignaturesBuilderLevel0::test_prudent(transactions.clone());
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert!(outcome.skipped_factor_sources.is_empty());
+        assert_eq!(
+            outcome
+                .successful_transactions
+                .into_iter()
+                .map(|t| t.intent_hash)
+                .collect::<IndexSet<_>>(),
+            transactions
+                .into_iter()
+                .map(|t| t.intent_hash)
+                .collect::<IndexSet<_>>()
+        );
+    }
+
+    /// Signs failing with the factor source with `id` with the `failures`, in
+    /// order, one per attempt.
+    fn failing_signer(
+        id: FactorSourceID,
+        failures: impl IntoIterator<Item = CommonError>,
+    ) -> std::sync::Arc<dyn FactorSourceSigner> {
+        std::sync::Arc::new(SimulatedFactorSourceSigner::default().with_failures(id, failures))
+    }
+
+    /// Signs taking `delay` to sign with the factor source with `id`.
+    fn slow_signer(
+        id: FactorSourceID,
+        delay: std::time::Duration,
+    ) -> std::sync::Arc<dyn FactorSourceSigner> {
+        std::sync::Arc::new(SimulatedFactorSourceSigner::default().with_delay(id, delay))
+    }
+
+    fn signer_options(signer: std::sync::Arc<dyn FactorSourceSigner>) -> SigningOptions {
+        SigningOptions {
+            signer,
+            ..Default::default()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn failing_factor_source_is_neglected_not_skipped() {
+        let a0 = Entity::a0();
+        let tx = TransactionIntent::new([a0.clone()]);
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [tx.clone()],
+            signer_options(failing_signer(
+                FactorSourceID::fs0(),
+                [CommonError::WrongPin],
+            )),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.skipped_factor_sources.is_empty());
+        assert_eq!(
+            outcome.neglected_factor_sources,
+            IndexMap::<FactorSourceID, NeglectReason>::from_iter([(
+                FactorSourceID::fs0(),
+                NeglectReason::Failure(CommonError::WrongPin)
+            )])
+        );
+        assert_eq!(
+            outcome.failed_transactions,
+            vec![FailedTransaction::new(tx.intent_hash, vec![a0.address])]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn prudent_user_a4_one_failing_threshold_factor_still_successful() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a4()])],
+            signer_options(failing_signer(
+                FactorSourceID::fs3(),
+                [CommonError::FactorSourceRemoved],
+            )),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(outcome.all_signatures().len(), 2);
+        assert_eq!(
+            outcome.neglected_factor_sources.keys().collect_vec(),
+            vec![&FactorSourceID::fs3()]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn neglected_factor_source_makes_remaining_factor_sources_required() {
+        use std::sync::{Arc, Mutex};
+
+        // Emily: 2 of fs0, fs3, fs5, sorted by kind: fs3 (Arculus), fs5
+        // (Yubikey), fs0 (Device). Lazy user skips fs3, is thus forced to sign
+        // with fs5, which fails, meaning fs0 alone cannot save the transaction,
+        // so it is skipped without prompting.
+        let prompted = Arc::new(Mutex::new(Vec::<(FactorSourceID, bool)>::new()));
+        let prompted_clone = prompted.clone();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Lazy(Laziness::new(
+                move |factor_source, invalid_tx_if_skipped| {
+                    prompted_clone
+                        .lock()
+                        .unwrap()
+                        .push((factor_source.id, invalid_tx_if_skipped.is_empty()));
+                    if invalid_tx_if_skipped.is_empty() {
+                        SigningUserInput::Skip
+                    } else {
+                        SigningUserInput::Sign
+                    }
+                },
+            )),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a4()])],
+            signer_options(failing_signer(
+                FactorSourceID::fs5(),
+                [CommonError::FactorSourceDisconnected],
+            )),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert_eq!(
+            prompted.lock().unwrap().clone(),
+            vec![
+                (FactorSourceID::fs3(), true),
+                (FactorSourceID::fs5(), false),
+            ]
+        );
+        assert!(!outcome.successful());
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([
+                (FactorSourceID::fs3(), SkipReason::User),
+                (
+                    FactorSourceID::fs0(),
+                    SkipReason::OnlyServesInvalidTransactions
+                )
+            ])
+        );
+        assert_eq!(
+            outcome.neglected_factor_sources.keys().collect_vec(),
+            vec![&FactorSourceID::fs5()]
+        );
+    }
+
+    fn retry_options(max_attempts_per_factor_source: usize) -> SigningOptions {
+        SigningOptions {
+            retry_policy: RetryPolicy::new(max_attempts_per_factor_source),
+            ..Default::default()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn prudent_user_retries_failing_factor_source_until_success() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            SigningOptions {
+                signer: failing_signer(
+                    FactorSourceID::fs0(),
+                    vec![CommonError::FactorSourceDisconnected; 2],
+                ),
+                ..retry_options(3)
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert!(outcome.neglected_factor_sources.is_empty());
+        assert_eq!(outcome.all_signatures().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn prudent_user_factor_source_neglected_when_out_of_attempts() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            SigningOptions {
+                signer: failing_signer(
+                    FactorSourceID::fs0(),
+                    [
+                        CommonError::FactorSourceDisconnected,
+                        CommonError::FactorSourceDisconnected,
+                        CommonError::WrongPin,
+                    ],
+                ),
+                ..retry_options(3)
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(!outcome.successful());
+        assert_eq!(
+            outcome.neglected_factor_sources,
+            IndexMap::<FactorSourceID, NeglectReason>::from_iter([(
+                FactorSourceID::fs0(),
+                NeglectReason::Failure(CommonError::WrongPin)
+            )])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn lazy_user_gives_up_on_failing_factor_source() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_sign_minimum(),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            SigningOptions {
+                signer: failing_signer(
+                    FactorSourceID::fs0(),
+                    [CommonError::FactorSourceDisconnected],
+                ),
+                ..retry_options(3)
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(!outcome.successful());
+        assert_eq!(
+            outcome.neglected_factor_sources.keys().collect_vec(),
+            vec![&FactorSourceID::fs0()]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn user_cancels_after_failure() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Lazy(
+                Laziness::new(|_, _| SigningUserInput::Sign)
+                    .on_failure(|_, _| SigningRetryInput::Cancel),
+            ),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            SigningOptions {
+                signer: failing_signer(
+                    FactorSourceID::fs0(),
+                    [CommonError::FactorSourceDisconnected],
+                ),
+                ..retry_options(3)
+            },
+        );
+        assert!(context.sign().await.unwrap().is_cancelled());
+    }
+
+    #[actix_rt::test]
+    async fn user_cancels_after_last_attempt_failed() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Lazy(
+                Laziness::new(|_, _| SigningUserInput::Sign)
+                    .on_failure(|_, _| SigningRetryInput::Cancel),
+            ),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            signer_options(failing_signer(
+                FactorSourceID::fs0(),
+                [CommonError::FactorSourceDisconnected],
+            )),
+        );
+        assert!(context.sign().await.unwrap().is_cancelled());
+    }
+
+    #[actix_rt::test]
+    async fn retry_after_last_attempt_failed_neglects_factor_source() {
+        let (user, transcript) = recording(TestSigningUser::Lazy(
+            Laziness::new(|_, _| SigningUserInput::Sign)
+                .on_failure(|_, _| SigningRetryInput::Retry),
+        ));
+        let context = SignaturesBuilderLevel0::new_with_options(
+            user,
+            FactorSource::all(),
+            IndexSet::from_iter([TransactionIntent::new([Entity::a0()])]),
+            signer_options(failing_signer(
+                FactorSourceID::fs0(),
+                [CommonError::WrongPin],
+            )),
+        )
+        .unwrap();
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        // A second attempt would have succeeded.
+        assert!(outcome.all_signatures().is_empty());
+        assert_eq!(
+            outcome.neglected_factor_sources,
+            IndexMap::<_, _>::from_iter([(
+                FactorSourceID::fs0(),
+                NeglectReason::Failure(CommonError::WrongPin)
+            )])
+        );
+        assert!(matches!(
+            transcript.snapshot().entries[1].prompt,
+            TranscribedPrompt::RetrySkipOrCancel {
+                can_retry: false,
+                ..
+            }
+        ));
+    }
+
+    fn concurrent_options(
+        kinds: impl IntoIterator<Item = FactorSourceKind>,
+        limit: usize,
+    ) -> SigningOptions {
+        SigningOptions {
+            concurrency: SigningConcurrency::concurrent_per_kind(kinds, limit),
+            ..Default::default()
+        }
+    }
+
+    fn all_kinds() -> IndexSet<FactorSourceKind> {
+        FactorSource::all().into_iter().map(|f| f.kind()).collect()
+    }
+
+    #[actix_rt::test]
+    async fn concurrent_prudent_user_same_signatures_as_sequential() {
+        for limit in [1, 2, 10] {
+            let transactions = Entity::all()
+                .into_iter()
+                .map(|e| TransactionIntent::new([e]))
+                .collect::<IndexSet<_>>();
+            let sequential = SignaturesBuilderLevel0::test_prudent(transactions.clone());
+            let concurrent = SignaturesBuilderLevel0::new_test_with_options(
+                TestSigningUser::Prudent,
+                FactorSource::all(),
+                transactions,
+                concurrent_options(all_kinds(), limit),
+            );
+            assert_eq!(
+                concurrent.sign().await.unwrap().signatures().unwrap(),
+                sequential.sign().await.unwrap().signatures().unwrap()
+            );
+        }
+    }
+
+    #[actix_rt::test]
+    async fn concurrent_lazy_user_skips_consistently() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_sign_minimum(),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            concurrent_options([FactorSourceKind::Ledger, FactorSourceKind::Arculus], 2),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(outcome.all_signatures().len(), 1);
+        assert_eq!(outcome.skipped_factor_sources.len(), 4);
+    }
+
+    /// Signs with `number_of_devices` Device factor sources - each taking
+    /// `delay` to sign - at most `limit` at a time, returning how long it took.
+    async fn time_signing_with_slow_devices(
+        number_of_devices: usize,
+        limit: usize,
+        delay: std::time::Duration,
+    ) -> std::time::Duration {
+        let devices = (0..number_of_devices)
+            .map(|_| FactorSource::device())
+            .collect::<IndexSet<_>>();
+        let entity = entity_with_matrix(MatrixOfFactorInstances::threshold_only(
+            devices.iter().map(|f| FactorInstance::new(0, f.id)),
+            number_of_devices as u8,
+        ));
+        let signer = devices
+            .iter()
+            .fold(SimulatedFactorSourceSigner::default(), |signer, device| {
+                signer.with_delay(device.id, delay)
+            });
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            devices,
+            [TransactionIntent::new([entity])],
+            SigningOptions {
+                signer: std::sync::Arc::new(signer),
+                ..concurrent_options([FactorSourceKind::Device], limit)
+            },
+        );
+        let start = tokio::time::Instant::now();
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        let elapsed = start.elapsed();
+        assert!(outcome.successful());
+        assert_eq!(outcome.all_signatures().len(), number_of_devices);
+        elapsed
+    }
+
+    // The clock is paused - and advanced whenever every task waits - so the
+    // elapsed time is exact, regardless of the load of the machine.
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_signing_takes_one_delay_per_batch_of_limit() {
+        const DEVICES: usize = 4;
+        let delay = std::time::Duration::from_millis(100);
+        for limit in [1, 2, 3, 4, 10] {
+            let batches = DEVICES.div_ceil(limit) as u32;
+            let elapsed = time_signing_with_slow_devices(DEVICES, limit, delay).await;
+            assert!(
+                elapsed == delay * batches,
+                "limit {limit}: took {elapsed:?}, expected {batches} x {delay:?}"
+            );
+        }
+    }
+
+    /// Signs like `DefaultFactorSourceSigner`, but returns `tamper`ed
+    /// signatures, e.g. of a host provided signer which is buggy.
+    #[derive(Debug)]
+    struct TamperingSigner {
+        tamper: fn(
+            IndexSet<SignatureByOwnedFactorForPayload>,
+        ) -> IndexSet<SignatureByOwnedFactorForPayload>,
+    }
+
+    #[async_trait::async_trait]
+    impl FactorSourceSigner for TamperingSigner {
+        async fn batch_sign(
+            &self,
+            factor_source: &FactorSource,
+            intent_hash: &IntentHash,
+            owned_instances: IndexSet<OwnedFactorInstance>,
+        ) -> Result<IndexSet<SignatureByOwnedFactorForPayload>> {
+            DefaultFactorSourceSigner
+                .batch_sign(factor_source, intent_hash, owned_instances)
+                .await
+                .map(self.tamper)
+        }
+    }
+
+    #[actix_rt::test]
+    async fn unexpected_signatures_of_signer_neglect_factor_source() {
+        let tampers: [fn(
+            IndexSet<SignatureByOwnedFactorForPayload>,
+        ) -> IndexSet<SignatureByOwnedFactorForPayload>; 4] = [
+            |_| IndexSet::new(),
+            |signatures| {
+                signatures
+                    .into_iter()
+                    .map(|s| SignatureByOwnedFactorForPayload {
+                        intent_hash: IntentHash::new(),
+                        ..s
+                    })
+                    .collect()
+            },
+            |signatures| {
+                signatures
+                    .into_iter()
+                    .map(|mut s| {
+                        s.owned_factor_instance.owner = Entity::a1().address;
+                        s
+                    })
+                    .collect()
+            },
+            |signatures| {
+                signatures
+                    .into_iter()
+                    .flat_map(|s| {
+                        let mut other = s.clone();
+                        other.owned_factor_instance.factor_instance.index += 1;
+                        [s, other]
+                    })
+                    .collect()
+            },
+        ];
+        for tamper in tampers {
+            let tx = TransactionIntent::new([Entity::a0()]);
+            let context = SignaturesBuilderLevel0::new_test_with_options(
+                TestSigningUser::Prudent,
+                FactorSource::all(),
+                [tx.clone()],
+                signer_options(std::sync::Arc::new(TamperingSigner { tamper })),
+            );
+            let outcome = context.sign().await.unwrap().signatures().unwrap();
+            assert!(outcome.all_signatures().is_empty());
+            assert_eq!(
+                outcome.neglected_factor_sources,
+                IndexMap::<_, _>::from_iter([(
+                    FactorSourceID::fs0(),
+                    NeglectReason::Failure(CommonError::UnexpectedSignatures {
+                        factor_source_id: FactorSourceID::fs0(),
+                        intent_hash: tx.intent_hash
+                    })
+                )])
+            );
+        }
+    }
+
+    #[actix_rt::test]
+    async fn concurrent_failing_factor_source_is_retried_after_others_signed() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            SigningOptions {
+                signer: failing_signer(
+                    FactorSourceID::fs1(),
+                    [CommonError::FactorSourceDisconnected],
+                ),
+                retry_policy: RetryPolicy::new(2),
+                concurrency: SigningConcurrency::concurrent_per_kind(all_kinds(), 3),
+                ..Default::default()
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert!(outcome.neglected_factor_sources.is_empty());
+        assert_eq!(outcome.all_signatures().len(), 5);
+    }
+
+    #[test]
+    fn concurrency_limit_for_kind() {
+        let sut = SigningConcurrency::concurrent_per_kind([FactorSourceKind::Device], 4);
+        assert_eq!(sut.limit_for_kind(FactorSourceKind::Device), Some(4));
+        assert_eq!(sut.limit_for_kind(FactorSourceKind::Ledger), None);
+        assert_eq!(
+            SigningConcurrency::Sequential.limit_for_kind(FactorSourceKind::Device),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Concurrency limit must not be 0")]
+    fn concurrency_limit_must_not_be_zero() {
+        _ = SigningConcurrency::concurrent_per_kind([FactorSourceKind::Device], 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Max attempts per factor source must not be 0")]
+    fn retry_policy_max_attempts_must_not_be_zero() {
+        _ = RetryPolicy::new(0);
+    }
+
+    #[test]
+    fn prompt_context_fails_for_factor_source_not_signed_with() {
+        let context =
+            SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a0()])]);
+        let ledger = FactorSource::fs1();
+        assert_eq!(
+            context.prompt_context(&ledger).err(),
+            Some(CommonError::UnknownFactorSource(ledger.id))
+        );
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[test]
+    fn signing_session_is_send_and_sync() {
+        assert_send_sync::<SignaturesBuilderLevel0>();
+        assert_send_sync::<SigningUser>();
+    }
+
+    #[test]
+    fn sign_future_is_send() {
+        let context =
+            SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a6()])]);
+        assert_send(&context.sign());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn sign_on_multi_threaded_runtime() {
+        let handles = Entity::all()
+            .into_iter()
+            .map(|entity| {
+                let context =
+                    SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([entity])]);
+                tokio::spawn(async move { context.sign().await.unwrap() })
+            })
+            .collect_vec();
+        for handle in handles {
+            let outcome = handle.await.unwrap().signatures().unwrap();
+            assert!(outcome.successful());
+        }
+    }
+
+    fn after_all_fulfilled_options(
+        after_all_transactions_fulfilled: AfterAllTransactionsFulfilled,
+    ) -> SigningOptions {
+        SigningOptions {
+            after_all_transactions_fulfilled,
+            ..Default::default()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn prudent_user_single_tx_a6_skip_remaining_once_fulfilled() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            after_all_fulfilled_options(AfterAllTransactionsFulfilled::SkipRemaining),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+
+        // Ledger (fs1) is an override factor, after having signed with it
+        // nothing more is needed.
+        assert_eq!(outcome.all_signatures().len(), 1);
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter(
+                [
+                    FactorSourceID::fs4(),
+                    FactorSourceID::fs3(),
+                    FactorSourceID::fs5(),
+                    FactorSourceID::fs0()
+                ]
+                .map(|id| (id, SkipReason::AllTransactionsFulfilled))
+            )
+        );
+    }
+
+    #[actix_rt::test]
+    async fn skip_remaining_once_fulfilled_does_not_prompt_user() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let prompts_clone = prompts.clone();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Lazy(Laziness::new(move |_, _| {
+                prompts_clone.fetch_add(1, Ordering::SeqCst);
+                SigningUserInput::Sign
+            })),
+            FactorSource::all(),
+            [
+                TransactionIntent::new([Entity::a1()]),
+                TransactionIntent::new([Entity::a6()]),
+            ],
+            after_all_fulfilled_options(AfterAllTransactionsFulfilled::SkipRemaining),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+
+        // fs1 (Ledger) is first, and fulfills both Bob and Grace.
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+        assert_eq!(outcome.skipped_factor_sources.len(), 4);
+    }
+
+    #[actix_rt::test]
+    async fn prompt_for_extra_signatures_once_fulfilled() {
+        let prudent = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            after_all_fulfilled_options(AfterAllTransactionsFulfilled::PromptForExtraSignatures),
+        );
+        assert_eq!(
+            prudent
+                .sign()
+                .await
+                .unwrap()
+                .signatures()
+                .unwrap()
+                .all_signatures()
+                .len(),
+            5
+        );
+
+        let lazy = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_sign_minimum(),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            after_all_fulfilled_options(AfterAllTransactionsFulfilled::PromptForExtraSignatures),
+        );
+        assert_eq!(
+            lazy.sign()
+                .await
+                .unwrap()
+                .signatures()
+                .unwrap()
+                .all_signatures()
+                .len(),
+            1
+        );
+    }
+
+    #[actix_rt::test]
+    async fn prompt_for_extra_signatures_per_factor_source_when_prompted_per_kind() {
+        let (user, transcript) = recording(TestSigningUser::Prudent);
+        let context = SignaturesBuilderLevel0::new_with_options(
+            user,
+            FactorSource::all(),
+            IndexSet::from_iter([TransactionIntent::new([Entity::a6()])]),
+            SigningOptions {
+                prompt_granularity: PromptGranularity::PerFactorSourceKind,
+                ..after_all_fulfilled_options(
+                    AfterAllTransactionsFulfilled::PromptForExtraSignatures,
+                )
+            },
+        )
+        .unwrap();
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert_eq!(outcome.all_signatures().len(), 5);
+        let prompts = transcript
+            .snapshot()
+            .entries
+            .into_iter()
+            .map(|e| match e.prompt {
+                TranscribedPrompt::SignOrSkipKind { kind, .. } => format!("kind {kind:?}"),
+                TranscribedPrompt::SignExtraOrSkip {
+                    factor_source_id, ..
+                } => format!("extra {factor_source_id:?}"),
+                other => panic!("Unexpected prompt {other:?}"),
+            })
+            .collect_vec();
+        // fs1 (Ledger) fulfills Grace, so every other factor source is extra.
+        assert_eq!(
+            prompts,
+            [
+                format!("kind {:?}", FactorSourceKind::Ledger),
+                format!("extra {:?}", FactorSourceID::fs3()),
+                format!("extra {:?}", FactorSourceID::fs4()),
+                format!("extra {:?}", FactorSourceID::fs5()),
+                format!("extra {:?}", FactorSourceID::fs0()),
+            ]
+        );
+    }
+
+    #[derive(Debug)]
+    struct DeviceFirstOrdering;
+    impl FactorSourceOrdering for DeviceFirstOrdering {
+        fn cmp_kinds(&self, lhs: &FactorSourceKind, rhs: &FactorSourceKind) -> std::cmp::Ordering {
+            let is_device = |k: &FactorSourceKind| *k == FactorSourceKind::Device;
+            is_device(rhs).cmp(&is_device(lhs)).then(lhs.cmp(rhs))
+        }
+    }
+
+    #[actix_rt::test]
+    async fn custom_ordering_decides_prompt_order() {
+        use std::sync::{Arc, Mutex};
+
+        let prompted = Arc::new(Mutex::new(Vec::<FactorSourceID>::new()));
+        let prompted_clone = prompted.clone();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Lazy(Laziness::new(move |factor_source, _| {
+                prompted_clone.lock().unwrap().push(factor_source.id);
+                SigningUserInput::Sign
+            })),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            SigningOptions {
+                ordering: Arc::new(DeviceFirstOrdering),
+                ..Default::default()
+            },
+        );
+        context.sign().await.unwrap();
+        assert_eq!(
+            prompted.lock().unwrap().first(),
+            Some(&FactorSourceID::fs0())
+        );
+        assert_eq!(
+            prompted
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|id| id.kind)
+                .collect_vec(),
+            vec![
+                FactorSourceKind::Device,
+                FactorSourceKind::Ledger,
+                FactorSourceKind::Arculus,
+                FactorSourceKind::Arculus,
+                FactorSourceKind::Yubikey,
+            ]
+        );
+    }
+
+    fn observed_options() -> (
+        SigningOptions,
+        futures::channel::mpsc::UnboundedReceiver<SigningEvent>,
+    ) {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let options = SigningOptions {
+            observer: Some(std::sync::Arc::new(sender)),
+            ..Default::default()
+        };
+        (options, receiver)
+    }
+
+    fn received(
+        mut receiver: futures::channel::mpsc::UnboundedReceiver<SigningEvent>,
+    ) -> Vec<SigningEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    #[actix_rt::test]
+    async fn events_prudent_user_single_tx_a0() {
+        let a0 = Entity::a0();
+        let tx = TransactionIntent::new([a0.clone()]);
+        let (options, receiver) = observed_options();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [tx.clone()],
+            options,
+        );
+        let signatures = context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .all_signatures();
+        let invalid_tx_if_skipped = IndexSet::from_iter([InvalidTransactionIfSkipped::new(
+            tx.intent_hash.clone(),
+            vec![a0.address.clone()],
+        )]);
+        assert_eq!(
+            received(receiver),
+            vec![
+                SigningEvent::FactorSourcePrompted {
+                    factor_source_id: FactorSourceID::fs0(),
+                    invalid_tx_if_skipped
+                },
+                SigningEvent::SignedWith {
+                    factor_source_id: FactorSourceID::fs0(),
+                    signatures
+                },
+                SigningEvent::TransactionFulfilled {
+                    intent_hash: tx.intent_hash,
+                    entities: vec![a0.address]
+                }
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn events_lazy_user_always_skip_single_tx_a0() {
+        let a0 = Entity::a0();
+        let tx = TransactionIntent::new([a0.clone()]);
+        let (options, receiver) = observed_options();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_always_skip(),
+            FactorSource::all(),
+            [tx.clone()],
+            options,
+        );
+        context.sign().await.unwrap();
+        let invalid_tx_if_skipped = IndexSet::from_iter([InvalidTransactionIfSkipped::new(
+            tx.intent_hash.clone(),
+            vec![a0.address.clone()],
+        )]);
+        assert_eq!(
+            received(receiver),
+            vec![
+                SigningEvent::FactorSourcePrompted {
+                    factor_source_id: FactorSourceID::fs0(),
+                    invalid_tx_if_skipped: invalid_tx_if_skipped.clone()
+                },
+                SigningEvent::Skipped {
+                    factor_source_id: FactorSourceID::fs0(),
+                    invalid_tx_if_skipped,
+                    reason: SkipReason::User
+                },
+                SigningEvent::TransactionBecameInvalid {
+                    intent_hash: tx.intent_hash,
+                    entities_which_failed_auth: vec![a0.address]
+                }
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn events_neglected_factor_source() {
+        let (options, receiver) = observed_options();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            SigningOptions {
+                signer: failing_signer(FactorSourceID::fs0(), [CommonError::WrongPin]),
+                ..options
+            },
+        );
+        context.sign().await.unwrap();
+        let events = received(receiver);
+        assert_eq!(
+            events[1],
+            SigningEvent::Neglected {
+                factor_source_id: FactorSourceID::fs0(),
+                reason: NeglectReason::Failure(CommonError::WrongPin)
+            }
+        );
+        assert!(matches!(
+            events[2],
+            SigningEvent::TransactionBecameInvalid { .. }
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn events_each_transaction_fulfilled_exactly_once() {
+        let transactions = Entity::all()
+            .into_iter()
+            .map(|e| TransactionIntent::new([e]))
+            .collect::<IndexSet<_>>();
+        let (options, receiver) = observed_options();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            transactions.clone(),
+            options,
+        );
+        context.sign().await.unwrap();
+        let fulfilled = received(receiver)
+            .into_iter()
+            .filter_map(|e| match e {
+                SigningEvent::TransactionFulfilled { intent_hash, .. } => Some(intent_hash),
+                _ => None,
+            })
+            .collect_vec();
+        assert_eq!(fulfilled.len(), transactions.len());
+        assert_eq!(
+            fulfilled.into_iter().collect::<IndexSet<_>>().len(),
+            transactions.len()
+        );
+    }
+
+    fn per_kind_options() -> SigningOptions {
+        SigningOptions {
+            prompt_granularity: PromptGranularity::PerFactorSourceKind,
+            ..Default::default()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn prudent_user_prompted_per_kind_signs_same_as_per_factor_source() {
+        let transactions = Entity::all()
+            .into_iter()
+            .map(|e| TransactionIntent::new([e]))
+            .collect::<IndexSet<_>>();
+        let per_kind = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            transactions.clone(),
+            per_kind_options(),
+        );
+        let per_factor_source = SignaturesBuilderLevel0::test_prudent(transactions);
+        let per_kind = per_kind.sign().await.unwrap().signatures().unwrap();
+        let per_factor_source = per_factor_source
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap();
+        assert!(per_kind.successful());
+        assert_eq!(
+            per_kind.all_signatures().len(),
+            per_factor_source.all_signatures().len()
+        );
+    }
+
+    #[actix_rt::test]
+    async fn lazy_user_prompted_per_kind_sign_minimum_a6() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_sign_minimum(),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            per_kind_options(),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(outcome.all_signatures().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn lazy_user_prompted_per_kind_cancel() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Lazy(Laziness::new(|_, _| SigningUserInput::Cancel)),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            per_kind_options(),
+        );
+        assert!(context.sign().await.unwrap().is_cancelled());
+    }
+
+    #[actix_rt::test]
+    async fn events_prompted_per_kind_all_of_kind_prompted_before_skipping() {
+        let (options, receiver) = observed_options();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_always_skip(),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            SigningOptions {
+                prompt_granularity: PromptGranularity::PerFactorSourceKind,
+                ..options
+            },
+        );
+        context.sign().await.unwrap();
+        let arculus_events = received(receiver)
+            .into_iter()
+            .filter_map(|e| match e {
+                SigningEvent::FactorSourcePrompted {
+                    factor_source_id, ..
+                } if factor_source_id.kind == FactorSourceKind::Arculus => {
+                    Some(("prompted", factor_source_id))
+                }
+                SigningEvent::Skipped {
+                    factor_source_id, ..
+                } if factor_source_id.kind == FactorSourceKind::Arculus => {
+                    Some(("skipped", factor_source_id))
+                }
+                _ => None,
+            })
+            .collect_vec();
+        assert_eq!(
+            arculus_events,
+            vec![
+                ("prompted", FactorSourceID::fs3()),
+                ("prompted", FactorSourceID::fs4()),
+                ("skipped", FactorSourceID::fs3()),
+                ("skipped", FactorSourceID::fs4()),
+            ]
+        );
+    }
+
+    fn timeout_options(
+        kind: FactorSourceKind,
+        user_decision: Option<std::time::Duration>,
+        signing: Option<std::time::Duration>,
+    ) -> SigningOptions {
+        SigningOptions {
+            timeouts: SigningTimeouts::default().with_kind(
+                kind,
+                FactorSourceKindTimeouts {
+                    user_decision,
+                    signing,
+                },
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn unresponsive_user_times_out_single_tx_a0() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Unresponsive,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            timeout_options(
+                FactorSourceKind::Device,
+                Some(std::time::Duration::from_millis(10)),
+                None,
+            ),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(!outcome.successful());
+        assert_eq!(
+            outcome.neglected_factor_sources,
+            IndexMap::<_, _>::from_iter([(FactorSourceID::fs0(), NeglectReason::TimedOut)])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn unresponsive_user_prompted_per_kind_times_out() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Unresponsive,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+            SigningOptions {
+                prompt_granularity: PromptGranularity::PerFactorSourceKind,
+                timeouts: SigningTimeouts::new(all_kinds().into_iter().map(|kind| {
+                    (
+                        kind,
+                        FactorSourceKindTimeouts {
+                            user_decision: Some(std::time::Duration::from_millis(10)),
+                            signing: None,
+                        },
+                    )
+                })),
+                ..Default::default()
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(!outcome.successful());
+        // Once the Yubikey times out the threshold cannot be reached, so the
+        // Device is skipped without prompting.
+        assert_eq!(outcome.neglected_factor_sources.len(), 4);
+        assert!(outcome
+            .neglected_factor_sources
+            .values()
+            .all(|reason| *reason == NeglectReason::TimedOut));
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([(
+                FactorSourceID::fs0(),
+                SkipReason::OnlyServesInvalidTransactions
+            )])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn hanging_factor_source_times_out_single_tx_a0() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            SigningOptions {
+                signer: slow_signer(FactorSourceID::fs0(), std::time::Duration::from_secs(60)),
+                ..timeout_options(
+                    FactorSourceKind::Device,
+                    None,
+                    Some(std::time::Duration::from_millis(10)),
+                )
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(!outcome.successful());
+        assert_eq!(
+            outcome.neglected_factor_sources,
+            IndexMap::<_, _>::from_iter([(FactorSourceID::fs0(), NeglectReason::TimedOut)])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn hanging_ledger_times_out_then_signs_with_arculus_a5() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a5()])],
+            SigningOptions {
+                signer: slow_signer(FactorSourceID::fs1(), std::time::Duration::from_secs(60)),
+                ..timeout_options(
+                    FactorSourceKind::Ledger,
+                    None,
+                    Some(std::time::Duration::from_millis(10)),
+                )
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(
+            outcome
+                .all_signatures()
+                .into_iter()
+                .map(|s| *s.factor_source_id())
+                .collect_vec(),
+            vec![FactorSourceID::fs4()]
+        );
+        assert_eq!(
+            outcome.neglected_factor_sources,
+            IndexMap::<_, _>::from_iter([(FactorSourceID::fs1(), NeglectReason::TimedOut)])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn slow_factor_source_without_timeout_signs() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            SigningOptions {
+                signer: slow_signer(FactorSourceID::fs0(), std::time::Duration::from_millis(20)),
+                ..timeout_options(
+                    FactorSourceKind::Ledger,
+                    Some(std::time::Duration::from_millis(1)),
+                    Some(std::time::Duration::from_millis(1)),
+                )
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+    }
+
+    /// Signs with the Ledger `fs1` then cancels, snapshotting the session.
+    async fn snapshot_after_ledger_then_cancel(
+        transactions: &IndexSet<TransactionIntent>,
+    ) -> SigningSessionSnapshot {
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::Lazy(Laziness::new(|factor_source, _| {
+                if factor_source.kind() == FactorSourceKind::Ledger {
+                    SigningUserInput::Sign
+                } else {
+                    SigningUserInput::Cancel
+                }
+            })),
+            FactorSource::all(),
+            transactions.clone(),
+        );
+        assert!(context.sign().await.unwrap().is_cancelled());
+        context.snapshot()
+    }
+
+    #[actix_rt::test]
+    async fn snapshot_json_roundtrip() {
+        let transactions = IndexSet::from_iter([
+            TransactionIntent::new([Entity::a1()]),
+            TransactionIntent::new([Entity::a0()]),
+        ]);
+        let snapshot = snapshot_after_ledger_then_cancel(&transactions).await;
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let deserialized = serde_json::from_str::<SigningSessionSnapshot>(&json).unwrap();
+        assert_eq!(deserialized, snapshot);
+    }
+
+    #[actix_rt::test]
+    async fn restored_session_continues_with_next_unevaluated_factor_source() {
+        let transactions = IndexSet::from_iter([
+            TransactionIntent::new([Entity::a1()]),
+            TransactionIntent::new([Entity::a0()]),
+        ]);
+        let snapshot = snapshot_after_ledger_then_cancel(&transactions).await;
+        let json = serde_json::to_string(&snapshot).unwrap();
+
+        let (options, receiver) = observed_options();
+        let restored = SignaturesBuilderLevel0::restore(
+            SigningUser::Test(TestSigningUser::Prudent),
+            FactorSource::all(),
+            transactions,
+            options,
+            serde_json::from_str(&json).unwrap(),
+        )
+        .unwrap();
+        let outcome = restored.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(
+            outcome
+                .all_signatures()
+                .into_iter()
+                .map(|s| *s.factor_source_id())
+                .collect_vec(),
+            vec![FactorSourceID::fs1(), FactorSourceID::fs0()]
+        );
+        let prompted = received(receiver)
+            .into_iter()
+            .filter_map(|e| match e {
+                SigningEvent::FactorSourcePrompted {
+                    factor_source_id, ..
+                } => Some(factor_source_id),
+                _ => None,
+            })
+            .collect_vec();
+        assert_eq!(prompted, vec![FactorSourceID::fs0()]);
+    }
+
+    #[actix_rt::test]
+    async fn restored_session_keeps_skipped_factor_sources() {
+        let transactions =
+            IndexSet::<TransactionIntent>::from_iter([TransactionIntent::new([Entity::a0()])]);
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::lazy_always_skip(),
+            FactorSource::all(),
+            transactions.clone(),
+        );
+        context.sign().await.unwrap();
+        let restored = SignaturesBuilderLevel0::restore(
+            SigningUser::Test(TestSigningUser::Prudent),
+            FactorSource::all(),
+            transactions,
+            SigningOptions::default(),
+            context.snapshot(),
+        )
+        .unwrap();
+        let outcome = restored.sign().await.unwrap().signatures().unwrap();
+        assert!(!outcome.successful());
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([(FactorSourceID::fs0(), SkipReason::User)])
+        );
+    }
+
+    #[test]
+    fn plan_single_tx_a6_all_optional_in_signing_order() {
+        let context =
+            SignaturesBuilderLevel0::test_prudent([TransactionIntent::new([Entity::a6()])]);
+        let plan = context.plan();
+        assert_eq!(
+            plan.factor_sources
+                .iter()
+                .map(|f| f.factor_source_id)
+                .collect_vec(),
+            vec![
+                FactorSourceID::fs1(),
+                FactorSourceID::fs3(),
+                FactorSourceID::fs4(),
+                FactorSourceID::fs5(),
+                FactorSourceID::fs0(),
+            ]
+        );
+        assert!(plan.mandatory().is_empty());
+        assert_eq!(plan.optional().len(), 5);
+    }
+
+    #[test]
+    fn plan_unsecurified_entities_are_mandatory() {
+        let a0 = Entity::a0();
+        let a1 = Entity::a1();
+        let tx0 = TransactionIntent::new([a0.clone()]);
+        let tx1 = TransactionIntent::new([a1.clone()]);
+        let context = SignaturesBuilderLevel0::test_prudent([tx0.clone(), tx1.clone()]);
+        let plan = context.plan();
+        assert_eq!(
+            plan.mandatory()
+                .into_iter()
+                .map(|f| (f.factor_source_id, f.serves.clone()))
+                .collect_vec(),
+            vec![
+                (
+                    FactorSourceID::fs1(),
+                    vec![ServedTransaction {
+                        intent_hash: tx1.intent_hash,
+                        entities: vec![a1.address]
+                    }]
+                ),
+                (
+                    FactorSourceID::fs0(),
+                    vec![ServedTransaction {
+                        intent_hash: tx0.intent_hash,
+                        entities: vec![a0.address]
+                    }]
+                ),
+            ]
+        );
+        assert!(plan.optional().is_empty());
+    }
+
+    #[test]
+    fn plan_factor_source_serving_many_entities_and_transactions() {
+        let a0 = Entity::a0();
+        let a2 = Entity::a2();
+        let a4 = Entity::a4();
+        let tx0 = TransactionIntent::new([a0.clone(), a2.clone()]);
+        let tx1 = TransactionIntent::new([a4.clone()]);
+        let context = SignaturesBuilderLevel0::test_prudent([tx0.clone(), tx1.clone()]);
+        let plan = context.plan();
+        let fs0 = plan
+            .factor_sources
+            .iter()
+            .find(|f| f.factor_source_id == FactorSourceID::fs0())
+            .unwrap();
+        assert_eq!(
+            fs0.serves,
+            vec![
+                ServedTransaction {
+                    intent_hash: tx0.intent_hash.clone(),
+                    entities: vec![a0.address.clone(), a2.address.clone()]
+                },
+                ServedTransaction {
+                    intent_hash: tx1.intent_hash,
+                    entities: vec![a4.address]
+                },
+            ]
+        );
+        assert_eq!(
+            fs0.invalid_tx_if_skipped,
+            IndexSet::<InvalidTransactionIfSkipped>::from_iter([InvalidTransactionIfSkipped::new(
+                tx0.intent_hash,
+                vec![a0.address, a2.address]
+            )])
+        );
+    }
+
+    #[test]
+    fn plan_without_user_has_requirement_of_every_entity() {
+        let a0 = Entity::a0();
+        let a4 = Entity::a4();
+        let tx0 = TransactionIntent::new([a0.clone()]);
+        let tx1 = TransactionIntent::new([a4.clone()]);
+        let transactions = IndexSet::from_iter([tx0.clone(), tx1.clone()]);
+        let plan = SigningPlan::new(
+            FactorSource::all(),
+            transactions.clone(),
+            SigningOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            plan,
+            SignaturesBuilderLevel0::test_prudent(transactions).plan()
+        );
+        assert_eq!(
+            plan.transactions,
+            vec![
+                PlannedTransaction {
+                    intent_hash: tx0.intent_hash,
+                    entities: vec![PlannedEntity {
+                        address: a0.address,
+                        requirement: Some(RemainingSignaturesRequirement {
+                            override_factors: IndexSet::new(),
+                            threshold_factors: IndexSet::from_iter([FactorSourceID::fs0()]),
+                            threshold: 1,
+                        })
+                    }]
+                },
+                PlannedTransaction {
+                    intent_hash: tx1.intent_hash,
+                    entities: vec![PlannedEntity {
+                        address: a4.address,
+                        requirement: Some(RemainingSignaturesRequirement {
+                            override_factors: IndexSet::new(),
+                            threshold_factors: IndexSet::from_iter([
+                                FactorSourceID::fs0(),
+                                FactorSourceID::fs3(),
+                                FactorSourceID::fs5(),
+                            ]),
+                            threshold: 2,
+                        })
+                    }]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_without_user_fails_for_factor_source_not_in_profile() {
+        assert_eq!(
+            SigningPlan::new(
+                IndexSet::from_iter([FactorSource::fs1()]),
+                IndexSet::from_iter([TransactionIntent::new([Entity::a0()])]),
+                SigningOptions::default(),
+            )
+            .err(),
+            Some(CommonError::UnknownFactorSource(FactorSourceID::fs0()))
+        );
+    }
+
+    #[test]
+    fn minimum_cost_factor_sources_a4_and_a6() {
+        let context = SignaturesBuilderLevel0::test_prudent([
+            TransactionIntent::new([Entity::a4()]),
+            TransactionIntent::new([Entity::a6()]),
+        ]);
+        assert_eq!(
+            context.minimum_cost_factor_sources(&FactorSourceCosts::default()),
+            Some(IndexSet::from_iter([
+                FactorSourceID::fs3(),
+                FactorSourceID::fs5()
+            ]))
+        );
+        assert_eq!(
+            context.minimum_cost_factor_sources(
+                &FactorSourceCosts::default().with_kind(FactorSourceKind::Arculus, 10)
+            ),
+            Some(IndexSet::from_iter([
+                FactorSourceID::fs5(),
+                FactorSourceID::fs0()
+            ]))
+        );
+        assert_eq!(
+            context.suggested_factor_sources_to_skip(&FactorSourceCosts::default()),
+            IndexSet::<FactorSourceID>::from_iter([
+                FactorSourceID::fs1(),
+                FactorSourceID::fs4(),
+                FactorSourceID::fs0()
+            ])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn prudent_user_skips_outside_minimum_cost_set_a4_and_a6() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            [
+                TransactionIntent::new([Entity::a4()]),
+                TransactionIntent::new([Entity::a6()]),
+            ],
+            SigningOptions {
+                skip_outside_minimum_cost_set: Some(FactorSourceCosts::default()),
+                ..Default::default()
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(
+            outcome
+                .all_signatures()
+                .into_iter()
+                .map(|s| *s.factor_source_id())
+                .collect::<IndexSet<_>>(),
+            IndexSet::<FactorSourceID>::from_iter([FactorSourceID::fs3(), FactorSourceID::fs5()])
+        );
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter(
+                [
+                    FactorSourceID::fs1(),
+                    FactorSourceID::fs4(),
+                    FactorSourceID::fs0()
+                ]
+                .map(|id| (id, SkipReason::OutsideMinimumCostSet))
+            )
+        );
+    }
+
+    #[actix_rt::test]
+    async fn invalid_transaction_does_not_stop_skipping_outside_minimum_cost_set() {
+        let on_second_ledger = entity_with_matrix(MatrixOfFactorInstances::single_threshold(
+            FactorInstance::new(7, FactorSourceID::fs2()),
+        ));
+        let doomed = TransactionIntent::new([on_second_ledger]);
+        // Skipping the second Ledger dooms its transaction, the other two
+        // can still succeed by signing with fs3 and fs5 only.
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Lazy(Laziness::new(|factor_source, _| {
+                if factor_source.id == FactorSourceID::fs2() {
+                    SigningUserInput::Skip
+                } else {
+                    SigningUserInput::Sign
+                }
+            })),
+            FactorSource::all(),
+            [
+                TransactionIntent::new([Entity::a4()]),
+                TransactionIntent::new([Entity::a6()]),
+                doomed.clone(),
+            ],
+            SigningOptions {
+                skip_outside_minimum_cost_set: Some(FactorSourceCosts::default()),
+                ..Default::default()
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert_eq!(outcome.successful_transactions.len(), 2);
+        assert_eq!(
+            outcome
+                .failed_transactions
+                .iter()
+                .map(|t| t.intent_hash.clone())
+                .collect_vec(),
+            vec![doomed.intent_hash]
+        );
+        assert_eq!(
+            outcome
+                .all_signatures()
+                .into_iter()
+                .map(|s| *s.factor_source_id())
+                .collect::<IndexSet<_>>(),
+            IndexSet::<FactorSourceID>::from_iter([FactorSourceID::fs3(), FactorSourceID::fs5()])
+        );
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([
+                (FactorSourceID::fs1(), SkipReason::OutsideMinimumCostSet),
+                (FactorSourceID::fs2(), SkipReason::User),
+                (FactorSourceID::fs4(), SkipReason::OutsideMinimumCostSet),
+                (FactorSourceID::fs0(), SkipReason::OutsideMinimumCostSet),
+            ])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn entity_is_blamed_when_skipping_factor_source_outside_minimum_cost_set() {
+        let entity = entity_with_matrix(MatrixOfFactorInstances::override_only([
+            FactorInstance::new(7, FactorSourceID::fs1()),
+            FactorInstance::new(7, FactorSourceID::fs0()),
+        ]));
+        let tx = TransactionIntent::new([entity.clone()]);
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_always_skip(),
+            FactorSource::all(),
+            [tx.clone()],
+            SigningOptions {
+                skip_outside_minimum_cost_set: Some(
+                    FactorSourceCosts::default().with_kind(FactorSourceKind::Ledger, 10),
+                ),
+                ..Default::default()
+            },
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([
+                (FactorSourceID::fs1(), SkipReason::OutsideMinimumCostSet),
+                (FactorSourceID::fs0(), SkipReason::User),
+            ])
+        );
+        assert_eq!(
+            outcome.failed_transactions,
+            vec![FailedTransaction::new(tx.intent_hash, vec![entity.address])]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn factor_source_only_serving_invalid_transaction_is_skipped_without_prompt() {
+        use std::sync::{Arc, Mutex};
+
+        let prompted = Arc::new(Mutex::new(Vec::<FactorSourceID>::new()));
+        let prompted_clone = prompted.clone();
+        let (options, receiver) = observed_options();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Lazy(Laziness::new(move |factor_source, _| {
+                prompted_clone.lock().unwrap().push(factor_source.id);
+                SigningUserInput::Skip
+            })),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a4()])],
+            options,
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert_eq!(
+            prompted.lock().unwrap().clone(),
+            vec![FactorSourceID::fs3(), FactorSourceID::fs5()]
+        );
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([
+                (FactorSourceID::fs3(), SkipReason::User),
+                (FactorSourceID::fs5(), SkipReason::User),
+                (
+                    FactorSourceID::fs0(),
+                    SkipReason::OnlyServesInvalidTransactions
+                ),
+            ])
+        );
+        let last_skip_reason = received(receiver)
+            .into_iter()
+            .filter_map(|e| match e {
+                SigningEvent::Skipped { reason, .. } => Some(reason),
+                _ => None,
+            })
+            .last();
+        assert_eq!(
+            last_skip_reason,
+            Some(SkipReason::OnlyServesInvalidTransactions)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn factor_source_also_serving_valid_transaction_is_prompted() {
+        let t0 = TransactionIntent::new([Entity::a4()]);
+        let t1 = TransactionIntent::new([Entity::a0()]);
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::Lazy(Laziness::new(|factor_source, _| {
+                if factor_source.id == FactorSourceID::fs0() {
+                    SigningUserInput::Sign
+                } else {
+                    SigningUserInput::Skip
+                }
+            })),
+            FactorSource::all(),
+            [t0.clone(), t1.clone()],
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert_eq!(
+            outcome
+                .successful_transactions
+                .iter()
+                .map(|t| t.intent_hash.clone())
+                .collect_vec(),
+            vec![t1.intent_hash]
+        );
+        assert_eq!(
+            outcome
+                .failed_transactions
+                .iter()
+                .map(|t| t.intent_hash.clone())
+                .collect_vec(),
+            vec![t0.intent_hash]
+        );
+        assert!(outcome
+            .skipped_factor_sources
+            .values()
+            .all(|reason| *reason == SkipReason::User));
+    }
+
+    fn all_or_nothing_options() -> SigningOptions {
+        SigningOptions {
+            batch_mode: BatchSigningMode::AllOrNothing,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn best_effort_is_default_batch_mode() {
+        assert_eq!(
+            SigningOptions::default().batch_mode,
+            BatchSigningMode::BestEffort
+        );
+    }
+
+    #[actix_rt::test]
+    async fn all_or_nothing_aborts_with_error_naming_invalid_transaction() {
+        let a0 = Entity::a0();
+        let t0 = TransactionIntent::new([a0.clone()]);
+        let t1 = TransactionIntent::new([Entity::a2()]);
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_always_skip(),
+            FactorSource::all(),
+            [t0.clone(), t1],
+            all_or_nothing_options(),
+        );
+        assert_eq!(
+            context.sign().await,
+            Err(CommonError::TransactionInvalid {
+                intent_hash: t0.intent_hash,
+                entities_which_failed_auth: vec![a0.address]
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn all_or_nothing_aborts_before_prompting_further_factor_sources() {
+        let (options, receiver) = observed_options();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::lazy_always_skip(),
+            FactorSource::all(),
+            [
+                TransactionIntent::new([Entity::a0()]),
+                TransactionIntent::new([Entity::a1()]),
+            ],
+            SigningOptions {
+                batch_mode: BatchSigningMode::AllOrNothing,
+                ..options
+            },
+        );
+        assert!(context.sign().await.is_err());
+        assert_eq!(
+            received(receiver)
+                .into_iter()
+                .filter(|e| matches!(e, SigningEvent::FactorSourcePrompted { .. }))
+                .count(),
+            1
+        );
+    }
+
+    #[actix_rt::test]
+    async fn best_effort_signs_valid_transactions_despite_invalid_ones() {
+        let t0 = TransactionIntent::new([Entity::a0()]);
+        let t1 = TransactionIntent::new([Entity::a1()]);
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::Lazy(Laziness::new(|factor_source, _| {
+                if factor_source.id == FactorSourceID::fs0() {
+                    SigningUserInput::Skip
+                } else {
+                    SigningUserInput::Sign
+                }
+            })),
+            FactorSource::all(),
+            [t0, t1.clone()],
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert_eq!(
+            outcome
+                .successful_transactions
+                .iter()
+                .map(|t| t.intent_hash.clone())
+                .collect_vec(),
+            vec![t1.intent_hash]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn all_or_nothing_prudent_user_signs_all() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::Prudent,
+            FactorSource::all(),
+            Entity::all()
+                .into_iter()
+                .map(|e| TransactionIntent::new([e]))
+                .collect::<IndexSet<_>>(),
+            all_or_nothing_options(),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.failed_transactions.is_empty());
+    }
+
+    fn new_prudent(
+        all_factor_sources_in_profile: impl IntoIterator<Item = FactorSource>,
+        transactions: impl IntoIterator<Item = TransactionIntent>,
+    ) -> Result<SignaturesBuilderLevel0> {
+        SignaturesBuilderLevel0::new(
+            SigningUser::Test(TestSigningUser::Prudent),
+            all_factor_sources_in_profile.into_iter().collect(),
+            transactions.into_iter().collect(),
+        )
+    }
+
+    fn entity_with_matrix(matrix: MatrixOfFactorInstances) -> Entity {
+        Entity {
+            security_state: EntitySecurityState::Securified(matrix),
+            ..Entity::a6()
+        }
+    }
+
+    #[test]
+    fn all_test_entities_have_valid_matrices() {
+        assert!(Entity::all()
+            .iter()
+            .all(|e| OwnedMatrixOfFactorInstances::from(e)
+                .matrix
+                .validate()
+                .is_ok()));
+    }
+
+    #[test]
+    fn new_fails_for_factor_source_not_in_profile() {
+        assert_eq!(
+            new_prudent(
+                [FactorSource::fs1()],
+                [TransactionIntent::new([Entity::a0()])]
+            )
+            .err(),
+            Some(CommonError::UnknownFactorSource(FactorSourceID::fs0()))
+        );
+    }
+
+    #[test]
+    fn new_fails_for_threshold_exceeding_threshold_factors() {
+        let entity = entity_with_matrix(MatrixOfFactorInstances {
+            threshold_factors: vec![FactorInstance::new(0, FactorSourceID::fs0())],
+            threshold: 2,
+            override_factors: Vec::new(),
+        });
+        assert_eq!(
+            new_prudent(
+                FactorSource::all(),
+                [TransactionIntent::new([entity.clone()])]
+            )
+            .err(),
+            Some(CommonError::InvalidMatrix {
+                address: entity.address,
+                reason: InvalidMatrixReason::ThresholdExceedsThresholdFactors {
+                    threshold: 2,
+                    number_of_threshold_factors: 1
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn new_fails_for_factor_source_both_override_and_threshold() {
+        let entity = entity_with_matrix(MatrixOfFactorInstances::new(
+            [FactorInstance::new(0, FactorSourceID::fs0())],
+            1,
+            [FactorInstance::new(0, FactorSourceID::fs0())],
+        ));
+        assert_eq!(
+            new_prudent(
+                FactorSource::all(),
+                [TransactionIntent::new([entity.clone()])]
+            )
+            .err(),
+            Some(CommonError::InvalidMatrix {
+                address: entity.address,
+                reason: InvalidMatrixReason::DuplicateFactorSource(FactorSourceID::fs0())
+            })
+        );
+    }
+
+    #[test]
+    fn new_fails_for_matrix_without_factors() {
+        let entity = entity_with_matrix(MatrixOfFactorInstances::override_only([]));
+        assert_eq!(
+            new_prudent(
+                FactorSource::all(),
+                [TransactionIntent::new([entity.clone()])]
+            )
+            .err(),
+            Some(CommonError::InvalidMatrix {
+                address: entity.address,
+                reason: InvalidMatrixReason::NoFactors
+            })
+        );
+    }
+
+    #[test]
+    fn new_fails_for_duplicate_entity_in_transaction() {
+        let a0 = Entity::a0();
+        let tx = TransactionIntent::new([a0.clone(), a0.clone()]);
+        assert_eq!(
+            new_prudent(FactorSource::all(), [tx.clone()]).err(),
+            Some(CommonError::DuplicateEntity {
+                intent_hash: tx.intent_hash,
+                address: a0.address
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn restore_fails_for_unknown_intent() {
+        let transactions = IndexSet::from_iter([
+            TransactionIntent::new([Entity::a1()]),
+            TransactionIntent::new([Entity::a0()]),
+        ]);
+        let snapshot = snapshot_after_ledger_then_cancel(&transactions).await;
+        let other = TransactionIntent::new([Entity::a0()]);
+        assert_eq!(
+            SignaturesBuilderLevel0::restore(
+                SigningUser::Test(TestSigningUser::Prudent),
+                FactorSource::all(),
+                IndexSet::from_iter([other]),
+                SigningOptions::default(),
+                snapshot,
+            )
+            .err(),
+            Some(CommonError::UnknownIntent(
+                transactions[0].intent_hash.clone()
+            ))
+        );
+    }
+
+    #[actix_rt::test]
+    async fn restore_fails_for_inconsistent_snapshot() {
+        let a0 = Entity::a0();
+        let transactions = IndexSet::from_iter([TransactionIntent::new([a0.clone()])]);
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::lazy_always_skip(),
+            FactorSource::all(),
+            transactions.clone(),
+        );
+        context.sign().await.unwrap();
+        let snapshot = context.snapshot();
+        let restore = |snapshot: SigningSessionSnapshot| {
+            SignaturesBuilderLevel0::restore(
+                SigningUser::Test(TestSigningUser::Prudent),
+                FactorSource::all(),
+                transactions.clone(),
+                SigningOptions::default(),
+                snapshot,
+            )
+            .err()
+        };
+
+        let mut not_signed_with = snapshot.clone();
+        not_signed_with
+            .neglected_factor_sources
+            .push(NeglectedFactorSource {
+                factor_source_id: FactorSourceID::fs1(),
+                reason: NeglectReason::TimedOut,
+            });
+        assert_eq!(
+            restore(not_signed_with),
+            Some(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::FactorSourceNotSignedWith(FactorSourceID::fs1())
+            ))
+        );
+
+        let mut not_of_entity = snapshot.clone();
+        not_of_entity.transactions[0].entities[0]
+            .skipped_factor_source_ids
+            .push(FactorSourceID::fs1());
+        assert_eq!(
+            restore(not_of_entity),
+            Some(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::FactorSourceNotOfEntity {
+                    address: a0.address.clone(),
+                    factor_source_id: FactorSourceID::fs1()
+                }
+            ))
+        );
+
+        let mut not_skipped_for_entity = snapshot.clone();
+        not_skipped_for_entity.transactions[0].entities[0]
+            .skipped_factor_source_ids
+            .clear();
+        assert_eq!(
+            restore(not_skipped_for_entity),
+            Some(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::SkippedFactorSourceMismatch {
+                    address: a0.address.clone(),
+                    factor_source_id: FactorSourceID::fs0()
+                }
+            ))
+        );
+
+        let mut skipped_only_for_entity = snapshot;
+        skipped_only_for_entity.skipped_factor_sources.clear();
+        assert_eq!(
+            restore(skipped_only_for_entity),
+            Some(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::SkippedFactorSourceMismatch {
+                    address: a0.address,
+                    factor_source_id: FactorSourceID::fs0()
+                }
+            ))
+        );
+    }
+
+    #[actix_rt::test]
+    async fn restore_fails_for_inconsistent_signatures() {
+        let a1 = Entity::a1();
+        let transactions = IndexSet::from_iter([
+            TransactionIntent::new([a1.clone()]),
+            TransactionIntent::new([Entity::a0()]),
+        ]);
+        // The Ledger fs1 has signed for Bob in the first transaction.
+        let snapshot = snapshot_after_ledger_then_cancel(&transactions).await;
+        let restore = |tamper: fn(&mut EntitySigningSnapshot)| {
+            let mut snapshot = snapshot.clone();
+            tamper(&mut snapshot.transactions[0].entities[0]);
+            SignaturesBuilderLevel0::restore(
+                SigningUser::Test(TestSigningUser::Prudent),
+                FactorSource::all(),
+                transactions.clone(),
+                SigningOptions::default(),
+                snapshot,
+            )
+            .err()
+        };
+
+        assert_eq!(
+            restore(|entity| entity.signatures[0].intent_hash = IntentHash::new()),
+            Some(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::SignatureOfOtherTransaction {
+                    intent_hash: transactions[0].intent_hash.clone(),
+                    address: a1.address.clone(),
+                    factor_source_id: FactorSourceID::fs1()
+                }
+            ))
+        );
+        assert_eq!(
+            restore(
+                |entity| entity.signatures[0].owned_factor_instance.owner = Entity::a0().address
+            ),
+            Some(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::SignatureOfOtherFactorInstance {
+                    address: a1.address.clone(),
+                    factor_source_id: FactorSourceID::fs1()
+                }
+            ))
+        );
+        assert_eq!(
+            restore(|entity| entity.signatures[0]
+                .owned_factor_instance
+                .factor_instance
+                .index += 1),
+            Some(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::SignatureOfOtherFactorInstance {
+                    address: a1.address.clone(),
+                    factor_source_id: FactorSourceID::fs1()
+                }
+            ))
+        );
+        assert_eq!(
+            restore(|entity| entity.skipped_factor_source_ids.push(FactorSourceID::fs1())),
+            Some(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::FactorSourceEvaluatedMoreThanOnce {
+                    address: a1.address,
+                    factor_source_id: FactorSourceID::fs1()
+                }
+            ))
+        );
+    }
+
+    #[actix_rt::test]
+    async fn scripted_user_answers_in_order() {
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::scripted([
+                (FactorSourceID::fs3(), SigningUserInput::Sign),
+                (FactorSourceID::fs5(), SigningUserInput::Skip),
+                (FactorSourceID::fs0(), SigningUserInput::Sign),
+            ]),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a4()])],
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(
+            outcome
+                .all_signatures()
+                .into_iter()
+                .map(|s| *s.factor_source_id())
+                .collect::<HashSet<_>>(),
+            HashSet::from_iter([FactorSourceID::fs0(), FactorSourceID::fs3()])
+        );
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([(FactorSourceID::fs5(), SkipReason::User)])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn scripted_user_by_kind() {
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::scripted([
+                (FactorSourceKind::Ledger, SigningUserInput::Sign),
+                (FactorSourceKind::Arculus, SigningUserInput::Skip),
+            ]),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a5()])],
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+    }
+
+    #[actix_rt::test]
+    async fn scripted_user_kind_entry_answers_every_factor_source_of_kind() {
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::scripted([
+                (FactorSourceKind::Ledger, SigningUserInput::Skip),
+                (FactorSourceKind::Arculus, SigningUserInput::Skip),
+                (FactorSourceKind::Yubikey, SigningUserInput::Sign),
+                (FactorSourceKind::Device, SigningUserInput::Sign),
+            ]),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a6()])],
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter(
+                [
+                    FactorSourceID::fs1(),
+                    FactorSourceID::fs3(),
+                    FactorSourceID::fs4()
+                ]
+                .map(|id| (id, SkipReason::User))
+            )
+        );
+    }
+
+    #[actix_rt::test]
+    async fn scripted_user_retries_then_cancels() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::scripted([
+                (
+                    FactorSourceID::fs0(),
+                    ScriptedAnswer::from(SigningUserInput::Sign),
+                ),
+                (FactorSourceID::fs0(), SigningRetryInput::Retry.into()),
+                (FactorSourceID::fs0(), SigningRetryInput::Cancel.into()),
+            ]),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            SigningOptions {
+                signer: failing_signer(
+                    FactorSourceID::fs0(),
+                    [CommonError::WrongPin, CommonError::WrongPin],
+                ),
+                ..retry_options(3)
+            },
+        );
+        assert!(context.sign().await.unwrap().is_cancelled());
+    }
+
+    #[actix_rt::test]
+    #[should_panic(expected = "Unexpected prompt")]
+    async fn scripted_user_panics_on_unscripted_retry_prompt() {
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::scripted([(FactorSourceID::fs0(), SigningUserInput::Sign)]),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+            signer_options(failing_signer(
+                FactorSourceID::fs0(),
+                [CommonError::WrongPin],
+            )),
+        );
+        _ = context.sign().await;
+    }
+
+    #[actix_rt::test]
+    #[should_panic(expected = "Unexpected prompt")]
+    async fn scripted_user_panics_on_unexpected_prompt() {
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::scripted([(FactorSourceID::fs1(), SigningUserInput::Sign)]),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+        );
+        _ = context.sign().await;
+    }
+
+    #[actix_rt::test]
+    #[should_panic(expected = "never consumed")]
+    async fn scripted_user_panics_on_unconsumed_entries() {
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::scripted([
+                (FactorSourceID::fs0(), SigningUserInput::Sign),
+                (FactorSourceID::fs1(), SigningUserInput::Sign),
+            ]),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a0()])],
+        );
+        _ = context.sign().await;
+    }
+
+    #[test]
+    fn random_user_skip_probability_per_kind() {
+        let randomness = Randomness::new(0)
+            .with_skip_probability(0.0)
+            .with_skip_probability_for_kind(FactorSourceKind::Ledger, 1.0);
+        assert_eq!(
+            randomness.skip_probability_for(FactorSourceKind::Ledger),
+            1.0
+        );
+        assert_eq!(
+            randomness.skip_probability_for(FactorSourceKind::Device),
+            0.0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Skip probability must be in 0.0..=1.0")]
+    fn random_user_rejects_invalid_skip_probability() {
+        _ = Randomness::new(0).with_skip_probability_for_kind(FactorSourceKind::Ledger, 1.5);
+    }
+
+    #[actix_rt::test]
+    async fn random_user_biased_per_kind() {
+        let context = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::Random(
+                Randomness::new(0)
+                    .with_skip_probability(0.0)
+                    .with_skip_probability_for_kind(FactorSourceKind::Ledger, 1.0),
+            ),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a5()])],
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([(FactorSourceID::fs1(), SkipReason::User)])
+        );
+        assert!(outcome.successful());
+    }
+
+    #[actix_rt::test]
+    async fn random_user_same_seed_same_outcome() {
+        let transactions = Entity::all()
+            .into_iter()
+            .map(|e| TransactionIntent::new([e]))
+            .collect::<IndexSet<_>>();
+        let sign = |seed| {
+            let context = SignaturesBuilderLevel0::new_test(
+                TestSigningUser::random(seed),
+                FactorSource::all(),
+                transactions.clone(),
+            );
+            async move { context.sign().await.unwrap().signatures().unwrap() }
+        };
+        for seed in 0..10 {
+            assert_eq!(sign(seed).await, sign(seed).await);
+        }
+    }
+
+    /// Randomly picked transactions - each with some of the entities of
+    /// `Entity::all()` - and a signer for which some factor sources fail to
+    /// sign, for a soak test session using `seed`.
+    fn soak_fixtures(seed: u64) -> (IndexSet<TransactionIntent>, SimulatedFactorSourceSigner) {
+        use rand::prelude::*;
+        use rand_chacha::ChaCha8Rng;
+        // Unlike `StdRng`, reproducible across `rand` versions and platforms.
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let entities = Entity::all().into_iter().collect_vec();
+        let transactions = (0..rng.gen_range(1..=4))
+            .map(|_| {
+                let number_of_entities = rng.gen_range(1..=3);
+                TransactionIntent::new(
+                    entities
+                        .choose_multiple(&mut rng, number_of_entities)
+                        .cloned(),
+                )
+            })
+            .collect();
+        let signer = FactorSource::all().into_iter().fold(
+            SimulatedFactorSourceSigner::default(),
+            |signer, factor_source| {
+                let number_of_failures = rng.gen_range(0..=2);
+                signer.with_failures(
+                    factor_source.id,
+                    vec![CommonError::WrongPin; number_of_failures],
+                )
+            },
+        );
+        (transactions, signer)
+    }
+
+    /// Returns `true` if signing with the factor sources `signed_with`
+    /// fulfills `matrix`, i.e. any override factor or `threshold` many
+    /// threshold factors signed.
+    fn satisfies_matrix(
+        matrix: &MatrixOfFactorInstances,
+        signed_with: &HashSet<FactorSourceID>,
+    ) -> bool {
+        let number_of_signed_threshold_factors = matrix
+            .threshold_factors
+            .iter()
+            .filter(|f| signed_with.contains(&f.factor_source_id))
+            .count();
+        matrix
+            .override_factors
+            .iter()
+            .any(|f| signed_with.contains(&f.factor_source_id))
+            || (matrix.threshold > 0
+                && number_of_signed_threshold_factors >= matrix.threshold as usize)
+    }
+
+    /// Panics - naming `seed` so that the session can be reproduced - if the
+    /// `outcome` of signing `transactions` violates an invariant.
+    fn assert_soak_invariants(
+        seed: u64,
+        transactions: &IndexSet<TransactionIntent>,
+        outcome: &SignaturesOutcome,
+    ) {
+        let successful = outcome
+            .successful_transactions
+            .iter()
+            .map(|t| t.intent_hash.clone())
+            .collect::<HashSet<_>>();
+        let failed = outcome
+            .failed_transactions
+            .iter()
+            .map(|t| t.intent_hash.clone())
+            .collect::<HashSet<_>>();
+        assert!(successful.is_disjoint(&failed), "seed {seed}");
+        assert_eq!(
+            successful.len() + failed.len(),
+            transactions.len(),
+            "seed {seed}: every transaction is either successful or failed"
+        );
+        assert!(
+            outcome
+                .skipped_factor_sources
+                .keys()
+                .all(|id| !outcome.neglected_factor_sources.contains_key(id)),
+            "seed {seed}: factor source both skipped and neglected"
+        );
+        let signed = outcome
+            .all_signatures()
+            .into_iter()
+            .map(|s| *s.factor_source_id())
+            .collect::<HashSet<_>>();
+        assert!(
+            signed.iter().all(|id| {
+                !outcome.skipped_factor_sources.contains_key(id)
+                    && !outcome.neglected_factor_sources.contains_key(id)
+            }),
+            "seed {seed}: factor source both signed with and skipped or neglected"
+        );
+        for failed in outcome.failed_transactions.iter() {
+            assert!(!failed.entities_which_failed_auth.is_empty(), "seed {seed}");
+            let transaction = transactions
+                .iter()
+                .find(|t| t.intent_hash == failed.intent_hash)
+                .unwrap();
+            for address in failed.entities_which_failed_auth.iter() {
+                let entity = transaction
+                    .entities_requiring_auth
+                    .iter()
+                    .find(|e| e.address == *address)
+                    .unwrap();
+                let matrix = OwnedMatrixOfFactorInstances::from(entity).matrix;
+                // Every factor source has been evaluated, so the ones neither
+                // skipped nor neglected have signed.
+                let signed_with = matrix
+                    .threshold_factors
+                    .iter()
+                    .chain(matrix.override_factors.iter())
+                    .map(|f| f.factor_source_id)
+                    .filter(|id| {
+                        !outcome.skipped_factor_sources.contains_key(id)
+                            && !outcome.neglected_factor_sources.contains_key(id)
+                    })
+                    .collect::<HashSet<_>>();
+                assert!(
+                    !satisfies_matrix(&matrix, &signed_with),
+                    "seed {seed}: transaction {:?} reported as failed, but {:?} satisfies its matrix",
+                    failed.intent_hash,
+                    address
+                );
+            }
+        }
+        for signed in outcome.successful_transactions.iter() {
+            let transaction = transactions
+                .iter()
+                .find(|t| t.intent_hash == signed.intent_hash)
+                .unwrap();
+            assert!(
+                signed
+                    .signatures
+                    .iter()
+                    .all(|s| s.intent_hash == signed.intent_hash),
+                "seed {seed}: signature of other transaction"
+            );
+            for entity in transaction.entities_requiring_auth.iter() {
+                let signed_with = signed
+                    .signatures
+                    .iter()
+                    .filter(|s| s.owned_factor_instance.owner == entity.address)
+                    .map(|s| *s.factor_source_id())
+                    .collect::<HashSet<_>>();
+                assert!(
+                    signed_with
+                        .iter()
+                        .all(|id| !outcome.skipped_factor_sources.contains_key(id)
+                            && !outcome.neglected_factor_sources.contains_key(id)),
+                    "seed {seed}: signature by skipped or neglected factor source"
+                );
+                let matrix = OwnedMatrixOfFactorInstances::from(entity).matrix;
+                assert!(
+                    satisfies_matrix(&matrix, &signed_with),
+                    "seed {seed}: transaction {:?} reported as valid, but {:?} does not satisfy its matrix",
+                    signed.intent_hash,
+                    entity.address
+                );
+            }
+        }
+    }
+
+    /// Signs `SOAK_SESSIONS` randomly picked sets of transactions, each with
+    /// a `TestSigningUser::Random` seeded and biased by the session index,
+    /// checking the invariants of each outcome.
+    async fn soak(options: impl Fn() -> SigningOptions) {
+        const SOAK_SESSIONS: u64 = 1000;
+        for seed in 0..SOAK_SESSIONS {
+            let (transactions, signer) = soak_fixtures(seed);
+            let skip_probability = (seed % 11) as f64 / 10.0;
+            let context = SignaturesBuilderLevel0::new_test_with_options(
+                TestSigningUser::Random(
+                    Randomness::new(seed).with_skip_probability(skip_probability.min(1.0)),
+                ),
+                FactorSource::all(),
+                transactions.clone(),
+                SigningOptions {
+                    signer: std::sync::Arc::new(signer),
+                    ..options()
+                },
+            );
+            let outcome = context
+                .sign()
+                .await
+                .unwrap()
+                .signatures()
+                .expect("Random user never cancels");
+            assert_soak_invariants(seed, &transactions, &outcome);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn soak_random_users() {
+        soak(SigningOptions::default).await;
+    }
+
+    #[actix_rt::test]
+    async fn soak_random_users_prompted_per_kind() {
+        soak(per_kind_options).await;
+    }
+
+    #[actix_rt::test]
+    async fn soak_random_users_skipping_outside_minimum_cost_set() {
+        soak(|| SigningOptions {
+            skip_outside_minimum_cost_set: Some(
+                FactorSourceCosts::default().with_kind(FactorSourceKind::Ledger, 10),
+            ),
+            ..Default::default()
+        })
+        .await;
+    }
+
+    fn recording(user: TestSigningUser) -> (SigningUser, SharedSigningTranscript) {
+        let recording_user = RecordingSigningUser::new(SigningUser::Test(user));
+        let transcript = recording_user.transcript();
+        (SigningUser::Recording(Box::new(recording_user)), transcript)
+    }
+
+    #[actix_rt::test]
+    async fn recorded_transcript_replays_same_outcome() {
+        let transactions = Entity::all()
+            .into_iter()
+            .map(|e| TransactionIntent::new([e]))
+            .collect::<IndexSet<_>>();
+        let (user, transcript) = recording(TestSigningUser::random(7));
+        let recorded =
+            SignaturesBuilderLevel0::new(user, FactorSource::all(), transactions.clone()).unwrap();
+        let recorded = recorded.sign().await.unwrap();
+
+        let json = serde_json::to_string(&transcript.snapshot()).unwrap();
+        let replayed = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::replaying(serde_json::from_str(&json).unwrap()),
+            FactorSource::all(),
+            transactions,
+        );
+        assert_eq!(replayed.sign().await.unwrap(), recorded);
+    }
+
+    #[actix_rt::test]
+    async fn recording_user_records_prompts_and_answers() {
+        let a0 = Entity::a0();
+        let tx = TransactionIntent::new([a0.clone()]);
+        let (user, transcript) = recording(TestSigningUser::Prudent);
+        let context = SignaturesBuilderLevel0::new_with_options(
+            user,
+            FactorSource::all(),
+            IndexSet::from_iter([tx.clone()]),
+            SigningOptions {
+                signer: failing_signer(FactorSourceID::fs0(), [CommonError::WrongPin]),
+                retry_policy: RetryPolicy::new(2),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        context.sign().await.unwrap();
+        let invalid_tx_if_skipped = IndexSet::from_iter([InvalidTransactionIfSkipped::new(
+            tx.intent_hash,
+            vec![a0.address],
+        )]);
+        assert_eq!(
+            transcript
+                .snapshot()
+                .entries
+                .into_iter()
+                .map(|e| e.prompt)
+                .collect_vec(),
+            vec![
+                TranscribedPrompt::SignOrSkip {
+                    factor_source_id: FactorSourceID::fs0(),
+                    invalid_tx_if_skipped: invalid_tx_if_skipped.clone(),
+                    answer: SigningUserInput::Sign
+                },
+                TranscribedPrompt::RetrySkipOrCancel {
+                    factor_source_id: FactorSourceID::fs0(),
+                    failure: CommonError::WrongPin,
+                    invalid_tx_if_skipped,
+                    can_retry: true,
+                    answer: SigningRetryInput::Retry
+                },
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn recorded_per_kind_transcript_replays() {
+        let transactions = IndexSet::from_iter([TransactionIntent::new([Entity::a6()])]);
+        let (user, transcript) = recording(TestSigningUser::lazy_sign_minimum());
+        let recorded = SignaturesBuilderLevel0::new_with_options(
+            user,
+            FactorSource::all(),
+            transactions.clone(),
+            per_kind_options(),
+        )
+        .unwrap();
+        let recorded = recorded.sign().await.unwrap();
+        assert!(transcript
+            .snapshot()
+            .entries
+            .iter()
+            .all(|e| matches!(e.prompt, TranscribedPrompt::SignOrSkipKind { .. })));
+
+        let replayed = SignaturesBuilderLevel0::new_test_with_options(
+            TestSigningUser::replaying(transcript.snapshot()),
+            FactorSource::all(),
+            transactions,
+            per_kind_options(),
+        );
+        assert_eq!(replayed.sign().await.unwrap(), recorded);
+    }
+
+    #[actix_rt::test]
+    #[should_panic(expected = "Unexpected prompt")]
+    async fn replay_panics_on_prompt_not_recorded() {
+        let (user, transcript) = recording(TestSigningUser::Prudent);
+        let recorded = SignaturesBuilderLevel0::new(
+            user,
+            FactorSource::all(),
+            IndexSet::from_iter([TransactionIntent::new([Entity::a0()])]),
+        )
+        .unwrap();
+        recorded.sign().await.unwrap();
+
+        let replayed = SignaturesBuilderLevel0::new_test(
+            TestSigningUser::replaying(transcript.snapshot()),
+            FactorSource::all(),
+            [TransactionIntent::new([Entity::a1()])],
+        );
+        _ = replayed.sign().await;
+    }
+
+    #[actix_rt::test]
+    async fn prompt_context_has_progress_of_transactions_and_entities() {
+        let a0 = Entity::a0();
+        let a1 = Entity::a1();
+        let t0 = TransactionIntent::new([a1.clone()]);
+        let t1 = TransactionIntent::new([a0.clone()]);
+        let transactions = IndexSet::from_iter([t0.clone(), t1.clone()]);
+        let snapshot = snapshot_after_ledger_then_cancel(&transactions).await;
+        let restored = SignaturesBuilderLevel0::restore(
+            SigningUser::Test(TestSigningUser::Prudent),
+            FactorSource::all(),
+            transactions,
+            SigningOptions::default(),
+            snapshot,
+        )
+        .unwrap();
+
+        let context = restored.prompt_context(&fs_at(0)).unwrap();
+        // Ledger, Device
+        assert_eq!(context.position, 1);
+        assert_eq!(context.number_of_factor_sources, 2);
+        assert_eq!(
+            context.number_of_transactions_with_status(TransactionSigningStatus::Fulfilled),
+            1
+        );
+        assert_eq!(
+            context.number_of_transactions_with_status(TransactionSigningStatus::Pending),
+            1
+        );
+        assert_eq!(
+            context
+                .transactions
+                .iter()
+                .map(|t| t.intent_hash.clone())
+                .collect_vec(),
+            vec![t0.intent_hash, t1.intent_hash.clone()]
+        );
+
+        let signed_by_ledger = &context.transactions[0].entities[0];
+        assert_eq!(signed_by_ledger.address, a1.address);
+        assert_eq!(signed_by_ledger.signed, 1);
+        assert_eq!(signed_by_ledger.remaining, 0);
+        assert_eq!(signed_by_ledger.remaining_requirement, None);
+
+        let pending = &context.transactions[1].entities[0];
+        assert_eq!(pending.address, a0.address);
+        assert_eq!(pending.signed, 0);
+        assert_eq!(pending.remaining, 1);
+        assert!(pending.remaining_requirement.is_some());
+
+        assert_eq!(
+            context
+                .invalid_tx_if_skipped
+                .into_iter()
+                .map(|i| i.intent_hash)
+                .collect_vec(),
+            vec![t1.intent_hash]
+        );
+    }
+
+    /// Signs with every factor source, remembering which it was prompted for.
+    #[derive(Clone, Default)]
+    struct HostUser {
+        prompted: std::sync::Arc<std::sync::Mutex<Vec<FactorSourceID>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl IsSigningUser for HostUser {
+        async fn sign_or_skip(
+            &self,
+            factor_source: &FactorSource,
+            _invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        ) -> SigningUserInput {
+            self.prompted.lock().unwrap().push(factor_source.id);
+            SigningUserInput::Sign
+        }
+
+        async fn retry_skip_or_cancel(
+            &self,
+            _factor_source: &FactorSource,
+            _failure: CommonError,
+            _invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+            _can_retry: bool,
+        ) -> SigningRetryInput {
+            SigningRetryInput::Cancel
+        }
+    }
+
+    #[actix_rt::test]
+    async fn host_user_signs_through_public_constructor() {
+        let host_user = HostUser::default();
+        let context = SignaturesBuilderLevel0::new(
+            SigningUser::host(host_user.clone()),
+            FactorSource::all(),
+            IndexSet::from_iter([TransactionIntent::new([Entity::a1(), Entity::a0()])]),
+        )
+        .unwrap();
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(
+            *host_user.prompted.lock().unwrap(),
+            vec![FactorSourceID::fs1(), FactorSourceID::fs0()]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn host_user_can_be_recorded() {
+        let recording_user = RecordingSigningUser::new(SigningUser::host(HostUser::default()));
+        let transcript = recording_user.transcript();
+        let context = SignaturesBuilderLevel0::new(
+            SigningUser::Recording(Box::new(recording_user)),
+            FactorSource::all(),
+            IndexSet::from_iter([TransactionIntent::new([Entity::a0()])]),
+        )
+        .unwrap();
+        assert!(context
+            .sign()
+            .await
+            .unwrap()
+            .signatures()
+            .unwrap()
+            .successful());
+        assert_eq!(transcript.snapshot().entries.len(), 1);
+    }
+
+    fn skips_all_ledgers() -> TestSigningUser {
+        TestSigningUser::Lazy(Laziness::new(|factor_source, _| {
+            if factor_source.kind() == FactorSourceKind::Ledger {
+                SigningUserInput::SkipAllOfKind
+            } else {
+                SigningUserInput::Sign
+            }
+        }))
+    }
+
+    #[actix_rt::test]
+    async fn skip_all_of_kind_skips_remaining_factor_sources_of_kind() {
+        let a0 = Entity::a0();
+        let a1 = Entity::a1();
+        let on_second_ledger = entity_with_matrix(MatrixOfFactorInstances::single_threshold(
+            FactorInstance::new(7, FactorSourceID::fs2()),
+        ));
+        let t0 = TransactionIntent::new([a1.clone()]);
+        let t1 = TransactionIntent::new([on_second_ledger.clone()]);
+        let t2 = TransactionIntent::new([a0]);
+        let (options, receiver) = observed_options();
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            skips_all_ledgers(),
+            FactorSource::all(),
+            [t0.clone(), t1.clone(), t2.clone()],
+            options,
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+
+        let prompted = received(receiver)
+            .into_iter()
+            .filter_map(|e| match e {
+                SigningEvent::FactorSourcePrompted {
+                    factor_source_id, ..
+                } => Some(factor_source_id),
+                _ => None,
+            })
+            .collect_vec();
+        assert_eq!(prompted, vec![FactorSourceID::fs1(), FactorSourceID::fs0()]);
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([
+                (FactorSourceID::fs1(), SkipReason::User),
+                (FactorSourceID::fs2(), SkipReason::User),
+            ])
+        );
+        assert_eq!(
+            outcome.failed_transactions,
+            vec![
+                FailedTransaction::new(t0.intent_hash, vec![a1.address]),
+                FailedTransaction::new(t1.intent_hash, vec![on_second_ledger.address]),
+            ]
+        );
+        assert_eq!(
+            outcome
+                .successful_transactions
+                .into_iter()
+                .map(|t| t.intent_hash)
+                .collect_vec(),
+            vec![t2.intent_hash]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn skip_all_of_kind_aborts_all_or_nothing_batch() {
+        let a1 = Entity::a1();
+        let t0 = TransactionIntent::new([a1.clone()]);
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            skips_all_ledgers(),
+            FactorSource::all(),
+            [t0.clone(), TransactionIntent::new([Entity::a0()])],
+            all_or_nothing_options(),
+        );
+        assert_eq!(
+            context.sign().await.err(),
+            Some(CommonError::TransactionInvalid {
+                intent_hash: t0.intent_hash,
+                entities_which_failed_auth: vec![a1.address]
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn skip_all_of_kind_prompted_per_kind_signs_with_none_after() {
+        let a5 = Entity::a5();
+        let user = TestSigningUser::scripted([
+            (
+                ScriptedPrompt::FactorSource(FactorSourceID::fs1()),
+                SigningUserInput::SkipAllOfKind,
+            ),
+            (
+                ScriptedPrompt::FactorSource(FactorSourceID::fs4()),
+                SigningUserInput::Sign,
+            ),
+        ]);
+        let context = SignaturesBuilderLevel0::new_test_with_options(
+            user,
+            FactorSource::all(),
+            [TransactionIntent::new([a5])],
+            per_kind_options(),
+        );
+        let outcome = context.sign().await.unwrap().signatures().unwrap();
+        assert!(outcome.successful());
+        assert_eq!(
+            outcome.skipped_factor_sources,
+            IndexMap::<_, _>::from_iter([(FactorSourceID::fs1(), SkipReason::User)])
+        );
+    }
 }
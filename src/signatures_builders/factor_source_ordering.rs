@@ -0,0 +1,106 @@
+use std::cmp::Ordering;
+
+use crate::prelude::*;
+use itertools::Itertools;
+
+/// Decides the "signing order" of factor sources, that is, which factor source
+/// kind the user signs with first, second etc, and within each kind, which
+/// factor source she signs with first, second etc.
+pub trait FactorSourceOrdering: std::fmt::Debug + Send + Sync {
+    /// Order of factor source kinds, defaults to the order of declaration of
+    /// `FactorSourceKind`, i.e. Ledger first and Device last.
+    fn cmp_kinds(&self, lhs: &FactorSourceKind, rhs: &FactorSourceKind) -> Ordering {
+        lhs.cmp(rhs)
+    }
+
+    /// Order of factor sources of the same kind, defaults to least recently
+    /// used first.
+    fn cmp_factor_sources(&self, lhs: &FactorSource, rhs: &FactorSource) -> Ordering {
+        lhs.cmp(rhs)
+    }
+
+    /// Groups `factor_sources` by kind, sorted according to `cmp_kinds`, with
+    /// each group sorted according to `cmp_factor_sources`.
+    fn factors_of_kind(
+        &self,
+        factor_sources: IndexSet<FactorSource>,
+    ) -> IndexMap<FactorSourceKind, IndexSet<FactorSource>> {
+        factor_sources
+            .into_iter()
+            .into_grouping_map_by(|f| f.kind())
+            .collect::<Vec<FactorSource>>()
+            .into_iter()
+            .sorted_by(|(lhs, _), (rhs, _)| self.cmp_kinds(lhs, rhs))
+            .map(|(kind, factor_sources)| {
+                let factor_sources = factor_sources
+                    .into_iter()
+                    .sorted_by(|lhs, rhs| self.cmp_factor_sources(lhs, rhs))
+                    .collect::<IndexSet<_>>();
+                (kind, factor_sources)
+            })
+            .collect()
+    }
+}
+
+/// The default signing order: Ledgers first - so that a user lacking access to
+/// her Ledger "fails fast" - Device last, and within each kind least recently
+/// used first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash)]
+pub struct DefaultFactorSourceOrdering;
+impl FactorSourceOrdering for DefaultFactorSourceOrdering {}
+
+/// Same kind order as `DefaultFactorSourceOrdering` but within each kind the
+/// most recently used factor source first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash)]
+pub struct MostRecentlyUsedFirstOrdering;
+impl FactorSourceOrdering for MostRecentlyUsedFirstOrdering {
+    fn cmp_factor_sources(&self, lhs: &FactorSource, rhs: &FactorSource) -> Ordering {
+        rhs.last_used.cmp(&lhs.last_used)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    fn ledger_used_at(secs: u64) -> FactorSource {
+        let mut factor_source = FactorSource::ledger();
+        factor_source.last_used = UNIX_EPOCH + Duration::from_secs(secs);
+        factor_source
+    }
+
+    #[test]
+    fn default_ordering_kinds_ledger_first_device_last() {
+        let sut = DefaultFactorSourceOrdering.factors_of_kind(FactorSource::all());
+        let kinds = sut.keys().cloned().collect_vec();
+        assert_eq!(kinds.first(), Some(&FactorSourceKind::Ledger));
+        assert_eq!(kinds.last(), Some(&FactorSourceKind::Device));
+        assert!(kinds.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn default_ordering_least_recently_used_first() {
+        let old = ledger_used_at(1);
+        let new = ledger_used_at(2);
+        let sut = DefaultFactorSourceOrdering
+            .factors_of_kind(IndexSet::from_iter([new.clone(), old.clone()]));
+        assert_eq!(
+            sut[&FactorSourceKind::Ledger],
+            IndexSet::<FactorSource>::from_iter([old, new])
+        );
+    }
+
+    #[test]
+    fn most_recently_used_first() {
+        let old = ledger_used_at(1);
+        let new = ledger_used_at(2);
+        let sut = MostRecentlyUsedFirstOrdering
+            .factors_of_kind(IndexSet::from_iter([old.clone(), new.clone()]));
+        assert_eq!(
+            sut[&FactorSourceKind::Ledger],
+            IndexSet::<FactorSource>::from_iter([new, old])
+        );
+    }
+}
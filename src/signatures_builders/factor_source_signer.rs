@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::prelude::*;
+
+/// Signs with a factor source, e.g. by talking to a Ledger over USB, which
+/// might fail, e.g. if the Ledger gets disconnected or an Arculus card is
+/// removed mid-tap.
+#[async_trait::async_trait]
+pub trait FactorSourceSigner: std::fmt::Debug + Send + Sync {
+    /// Signs `intent_hash` with all `owned_instances` of `factor_source`.
+    async fn batch_sign(
+        &self,
+        factor_source: &FactorSource,
+        intent_hash: &IntentHash,
+        owned_instances: IndexSet<OwnedFactorInstance>,
+    ) -> Result<IndexSet<SignatureByOwnedFactorForPayload>>;
+}
+
+/// Signs using `FactorSource::batch_sign`, never failing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash)]
+pub struct DefaultFactorSourceSigner;
+
+#[async_trait::async_trait]
+impl FactorSourceSigner for DefaultFactorSourceSigner {
+    async fn batch_sign(
+        &self,
+        factor_source: &FactorSource,
+        intent_hash: &IntentHash,
+        owned_instances: IndexSet<OwnedFactorInstance>,
+    ) -> Result<IndexSet<SignatureByOwnedFactorForPayload>> {
+        Ok(factor_source.batch_sign(intent_hash, owned_instances).await)
+    }
+}
+
+/// Emulates factor sources failing - e.g. a Ledger being disconnected or a
+/// wrong PIN being entered - or taking long to sign, e.g. a hanging Ledger.
+///
+/// Each call to `batch_sign` consumes the next failure of the factor source,
+/// if any, so that a failing factor source can succeed once retried.
+#[derive(Clone, Debug, Default)]
+pub struct SimulatedFactorSourceSigner {
+    failures: Arc<Mutex<HashMap<FactorSourceID, VecDeque<CommonError>>>>,
+    delays: HashMap<FactorSourceID, Duration>,
+}
+
+impl SimulatedFactorSourceSigner {
+    /// Fails to sign with the factor source with `id` with the `failures`,
+    /// in order, one per call to `batch_sign`.
+    pub fn with_failures(
+        self,
+        id: FactorSourceID,
+        failures: impl IntoIterator<Item = CommonError>,
+    ) -> Self {
+        self.failures
+            .lock()
+            .unwrap()
+            .insert(id, failures.into_iter().collect());
+        self
+    }
+
+    /// Takes `delay` to sign with the factor source with `id`, per call to
+    /// `batch_sign`.
+    pub fn with_delay(mut self, id: FactorSourceID, delay: Duration) -> Self {
+        self.delays.insert(id, delay);
+        self
+    }
+
+    fn next_failure(&self, id: &FactorSourceID) -> Option<CommonError> {
+        self.failures
+            .lock()
+            .unwrap()
+            .get_mut(id)
+            .and_then(|failures| failures.pop_front())
+    }
+}
+
+#[async_trait::async_trait]
+impl FactorSourceSigner for SimulatedFactorSourceSigner {
+    async fn batch_sign(
+        &self,
+        factor_source: &FactorSource,
+        intent_hash: &IntentHash,
+        owned_instances: IndexSet<OwnedFactorInstance>,
+    ) -> Result<IndexSet<SignatureByOwnedFactorForPayload>> {
+        if let Some(delay) = self.delays.get(&factor_source.id) {
+            actix_rt::time::sleep(*delay).await;
+        }
+        if let Some(failure) = self.next_failure(&factor_source.id) {
+            return Err(failure);
+        }
+        DefaultFactorSourceSigner
+            .batch_sign(factor_source, intent_hash, owned_instances)
+            .await
+    }
+}
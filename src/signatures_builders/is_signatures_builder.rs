@@ -7,7 +7,11 @@ pub trait IsSignaturesBuilder {
         factor_source: &FactorSource,
     ) -> IndexSet<Self::InvalidIfSkipped>;
 
-    fn skip_factor_sources(&self, factor_source: &FactorSource);
+    fn skip_factor_sources(&self, factor_source: &FactorSource, reason: SkipReason);
+
+    /// Records that signing with `factor_source` failed, which - just like
+    /// skipping - means that it will not contribute any signatures.
+    fn neglect_factor_source(&self, factor_source: &FactorSource, reason: NeglectReason);
     fn has_fulfilled_signatures_requirement(&self) -> bool;
     fn signatures(&self) -> IndexSet<SignatureByOwnedFactorForPayload>;
     fn append_signature(&self, signature: SignatureByOwnedFactorForPayload);
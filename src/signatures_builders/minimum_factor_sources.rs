@@ -0,0 +1,191 @@
+use crate::prelude::*;
+
+/// The cost of signing with a factor source, per kind, e.g. fetching a Ledger
+/// is more costly for the user than signing with her phone. Kinds without a
+/// cost cost `1`, i.e. by default the cheapest set of factor sources is the
+/// smallest one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FactorSourceCosts {
+    per_kind: IndexMap<FactorSourceKind, u32>,
+}
+
+impl FactorSourceCosts {
+    pub fn new(per_kind: impl IntoIterator<Item = (FactorSourceKind, u32)>) -> Self {
+        Self {
+            per_kind: per_kind.into_iter().collect(),
+        }
+    }
+
+    /// Returns a copy of these costs using `cost` for `kind`.
+    pub fn with_kind(mut self, kind: FactorSourceKind, cost: u32) -> Self {
+        self.per_kind.insert(kind, cost);
+        self
+    }
+
+    pub fn cost_of(&self, kind: FactorSourceKind) -> u32 {
+        self.per_kind.get(&kind).copied().unwrap_or(1)
+    }
+}
+
+/// What is still needed for an entity to fulfill its signatures requirement,
+/// given the factor sources already signed with, skipped or neglected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemainingSignaturesRequirement {
+    /// Signing with any of these fulfills the requirement.
+    pub override_factors: IndexSet<FactorSourceID>,
+
+    /// Signing with `threshold` of these fulfills the requirement.
+    pub threshold_factors: IndexSet<FactorSourceID>,
+
+    /// Number of additional signatures by `threshold_factors` needed.
+    pub threshold: usize,
+}
+
+impl RemainingSignaturesRequirement {
+    fn is_fulfilled_by(&self, signing_with: &[FactorSourceID]) -> bool {
+        signing_with
+            .iter()
+            .any(|id| self.override_factors.contains(id))
+            || signing_with
+                .iter()
+                .filter(|id| self.threshold_factors.contains(*id))
+                .count()
+                >= self.threshold
+    }
+}
+
+/// Finds the cheapest set of `candidates` - according to `costs` - which
+/// fulfills every requirement in `requirements`, this is the (weighted) set
+/// cover problem, solved exactly using branch and bound, which is
+/// exponential in the number of candidates in the worst case, which is fine
+/// for the handful of factor sources of a Profile.
+///
+/// Among sets of equal cost, the one preferring candidates early in
+/// `candidates` is returned, i.e. pass them in "signing order".
+///
+/// Returns `None` if not even all candidates fulfill all requirements.
+pub fn minimum_cost_factor_sources(
+    candidates: &[FactorSource],
+    requirements: &[RemainingSignaturesRequirement],
+    costs: &FactorSourceCosts,
+) -> Option<IndexSet<FactorSourceID>> {
+    let candidates = candidates
+        .iter()
+        .filter(|f| {
+            requirements
+                .iter()
+                .any(|r| r.override_factors.contains(&f.id) || r.threshold_factors.contains(&f.id))
+        })
+        .map(|f| (f.id, costs.cost_of(f.kind())))
+        .collect::<Vec<_>>();
+
+    let all = candidates.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+    if !requirements.iter().all(|r| r.is_fulfilled_by(&all)) {
+        return None;
+    }
+
+    let mut search = MinimumCostSearch {
+        candidates: &candidates,
+        requirements,
+        best: None,
+    };
+    search.branch(0, &mut Vec::new(), 0);
+    search
+        .best
+        .map(|(ids, _)| ids.into_iter().collect::<IndexSet<_>>())
+}
+
+struct MinimumCostSearch<'a> {
+    candidates: &'a [(FactorSourceID, u32)],
+    requirements: &'a [RemainingSignaturesRequirement],
+    best: Option<(Vec<FactorSourceID>, u32)>,
+}
+
+impl MinimumCostSearch<'_> {
+    fn branch(&mut self, index: usize, chosen: &mut Vec<FactorSourceID>, cost: u32) {
+        if self.best.as_ref().is_some_and(|(_, best)| cost >= *best) {
+            return;
+        }
+        if self.requirements.iter().all(|r| r.is_fulfilled_by(chosen)) {
+            self.best = Some((chosen.clone(), cost));
+            return;
+        }
+        let Some((id, id_cost)) = self.candidates.get(index) else {
+            return;
+        };
+        // Bound: prune if the chosen ones and all remaining candidates
+        // together cannot fulfill every requirement.
+        let reachable = chosen
+            .iter()
+            .chain(self.candidates[index..].iter().map(|(id, _)| id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !self
+            .requirements
+            .iter()
+            .all(|r| r.is_fulfilled_by(&reachable))
+        {
+            return;
+        }
+
+        chosen.push(*id);
+        self.branch(index + 1, chosen, cost + id_cost);
+        chosen.pop();
+        self.branch(index + 1, chosen, cost);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs_at;
+
+    fn requirement(
+        override_factors: impl IntoIterator<Item = FactorSourceID>,
+        threshold_factors: impl IntoIterator<Item = FactorSourceID>,
+        threshold: usize,
+    ) -> RemainingSignaturesRequirement {
+        RemainingSignaturesRequirement {
+            override_factors: override_factors.into_iter().collect(),
+            threshold_factors: threshold_factors.into_iter().collect(),
+            threshold,
+        }
+    }
+
+    #[test]
+    fn minimum_cardinality_prefers_shared_override() {
+        let fs = (0..5).map(fs_at).collect::<Vec<_>>();
+        // Both entities can be fulfilled by fs4 alone, or by two threshold
+        // factors each.
+        let requirements = [
+            requirement([fs[4].id], [fs[0].id, fs[1].id], 2),
+            requirement([fs[4].id], [fs[2].id, fs[3].id], 2),
+        ];
+        assert_eq!(
+            minimum_cost_factor_sources(&fs, &requirements, &FactorSourceCosts::default()),
+            Some(IndexSet::from_iter([fs[4].id]))
+        );
+    }
+
+    #[test]
+    fn minimum_cost_avoids_expensive_kind() {
+        let fs = (0..5).map(fs_at).collect::<Vec<_>>();
+        let requirements = [requirement([fs[4].id], [fs[0].id, fs[2].id], 2)];
+        // fs4 is an Arculus, fs0 a Device and fs2 a Ledger.
+        let costs = FactorSourceCosts::default().with_kind(FactorSourceKind::Arculus, 10);
+        assert_eq!(
+            minimum_cost_factor_sources(&fs, &requirements, &costs),
+            Some(IndexSet::from_iter([fs[0].id, fs[2].id]))
+        );
+    }
+
+    #[test]
+    fn unfulfillable_requirement_has_no_minimum() {
+        let fs = (0..2).map(fs_at).collect::<Vec<_>>();
+        let requirements = [requirement([], [fs[0].id], 2)];
+        assert_eq!(
+            minimum_cost_factor_sources(&fs, &requirements, &FactorSourceCosts::default()),
+            None
+        );
+    }
+}
@@ -1,12 +1,28 @@
+mod factor_source_ordering;
+mod factor_source_signer;
 mod is_signatures_builder;
+mod minimum_factor_sources;
 mod signatures_builder_level0;
 mod signatures_builder_level1;
 mod signatures_builder_level2;
+mod signing_events;
+mod signing_options;
+mod signing_plan;
+mod signing_prompt_context;
+mod signing_session_snapshot;
 
+pub use factor_source_ordering::*;
+pub use factor_source_signer::*;
 pub use is_signatures_builder::*;
+pub use minimum_factor_sources::*;
 pub use signatures_builder_level0::*;
 pub use signatures_builder_level1::*;
 pub use signatures_builder_level2::*;
+pub use signing_events::*;
+pub use signing_options::*;
+pub use signing_plan::*;
+pub use signing_prompt_context::*;
+pub use signing_session_snapshot::*;
 
 #[cfg(test)]
 mod tests {
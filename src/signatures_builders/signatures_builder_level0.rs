@@ -1,7 +1,21 @@
-use std::cell::RefCell;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::sync::RwLock;
+use std::time::Duration;
 
 use crate::prelude::*;
+use futures::stream::{self, StreamExt};
 use itertools::Itertools;
+use tracing::{debug, info, warn};
+
+/// Why signing stopped before every factor source was evaluated.
+enum Interruption {
+    /// The user cancelled the signing process.
+    Cancelled,
+
+    /// A transaction became invalid in `BatchSigningMode::AllOrNothing`.
+    Aborted(CommonError),
+}
 
 /// Root Signing Context: Aggregates over multiple Transactions.
 pub struct SignaturesBuilderLevel0 {
@@ -11,12 +25,16 @@ pub struct SignaturesBuilderLevel0 {
     /// asynchronously sign.
     user: SigningUser,
 
-    /// Factor sources grouped by kind, sorted according to "signing order",
-    /// that is, we want to control which factor source kind users signs with
-    /// first, second etc, e.g. typically we prompt user to sign with Ledgers
-    /// first, and if a user might lack access to that Ledger device, then it is
-    /// best to "fail fast", otherwise we might waste the users time, if she has
-    /// e.g. answered security questions and then is asked to sign with a Ledger
+    /// Configuration of this signing process, e.g. how to handle failures.
+    options: SigningOptions,
+
+    /// Factor sources grouped by kind, sorted according to "signing order" -
+    /// decided by `SigningOptions::ordering` - that is, we want to control
+    /// which factor source kind users signs with first, second etc, e.g.
+    /// typically we prompt user to sign with Ledgers first, and if a user
+    /// might lack access to that Ledger device, then it is best to "fail
+    /// fast", otherwise we might waste the users time, if she has e.g.
+    /// answered security questions and then is asked to sign with a Ledger
     /// she might not have handy at the moment - or might not be in front of a
     /// computer and thus unable to make a connection between the Radix Wallet
     /// and a Ledger device.
@@ -38,7 +56,13 @@ pub struct SignaturesBuilderLevel0 {
     factor_to_payloads: HashMap<FactorSourceID, IndexSet<IntentHash>>,
 
     /// Lookup from payload (TXID) to signatures builders.
-    builders_level_0: RefCell<HashMap<IntentHash, SignaturesBuilderLevel1>>,
+    builders_level_0: RwLock<IndexMap<IntentHash, SignaturesBuilderLevel1>>,
+
+    /// IDs of the factor sources which were skipped, and why, in order.
+    skipped_factor_sources: RwLock<IndexMap<FactorSourceID, SkipReason>>,
+
+    /// IDs of the factor sources which failed to sign, and why, in order.
+    neglected_factor_sources: RwLock<IndexMap<FactorSourceID, NeglectReason>>,
 }
 
 impl SignaturesBuilderLevel0 {
@@ -46,8 +70,26 @@ impl SignaturesBuilderLevel0 {
         user: SigningUser,
         all_factor_sources_in_profile: IndexSet<FactorSource>,
         transactions: IndexSet<TransactionIntent>,
-    ) -> Self {
-        let mut builders_level_0 = HashMap::<IntentHash, SignaturesBuilderLevel1>::new();
+    ) -> Result<Self> {
+        Self::new_with_options(
+            user,
+            all_factor_sources_in_profile,
+            transactions,
+            SigningOptions::default(),
+        )
+    }
+
+    /// Fails if the `transactions` are inconsistent with the Profile, e.g. an
+    /// entity controlled by a factor source not in
+    /// `all_factor_sources_in_profile`, an invalid matrix or an entity
+    /// requiring auth more than once in a transaction.
+    pub fn new_with_options(
+        user: SigningUser,
+        all_factor_sources_in_profile: IndexSet<FactorSource>,
+        transactions: IndexSet<TransactionIntent>,
+        options: SigningOptions,
+    ) -> Result<Self> {
+        let mut builders_level_0 = IndexMap::<IntentHash, SignaturesBuilderLevel1>::new();
 
         let all_factor_sources_in_profile = all_factor_sources_in_profile
             .into_iter()
@@ -58,42 +100,53 @@ impl SignaturesBuilderLevel0 {
 
         let mut used_factor_sources = HashSet::<FactorSource>::new();
 
-        let mut use_factor_in_tx = |id: &FactorSourceID, txid: &IntentHash| {
+        let mut use_factor_in_tx = |id: &FactorSourceID, txid: &IntentHash| -> Result<()> {
             if let Some(ref mut txids) = factor_to_payloads.get_mut(id) {
                 txids.insert(txid.clone());
             } else {
-                factor_to_payloads.insert(id.clone(), IndexSet::from_iter([txid.clone()]));
+                factor_to_payloads.insert(*id, IndexSet::from_iter([txid.clone()]));
             }
 
             assert!(!factor_to_payloads.is_empty());
 
             let factor_source = all_factor_sources_in_profile
                 .get(id)
-                .expect("Should have all factor sources");
+                .ok_or(CommonError::UnknownFactorSource(*id))?;
             used_factor_sources.insert(factor_source.clone());
 
             assert!(!used_factor_sources.is_empty());
+            Ok(())
         };
 
         for transaction in transactions {
             let mut builders_level_2 =
-                HashMap::<AccountAddressOrIdentityAddress, SignaturesBuilderLevel2>::new();
+                IndexMap::<AccountAddressOrIdentityAddress, SignaturesBuilderLevel2>::new();
 
             for entity in transaction.clone().entities_requiring_auth {
                 let address = entity.address;
+                if builders_level_2.contains_key(&address) {
+                    return Err(CommonError::DuplicateEntity {
+                        intent_hash: transaction.intent_hash,
+                        address,
+                    });
+                }
                 match entity.security_state {
                     EntitySecurityState::Securified(sec) => {
                         let primary_role_matrix = sec;
+                        primary_role_matrix.validate().map_err(|reason| {
+                            CommonError::InvalidMatrix {
+                                address: address.clone(),
+                                reason,
+                            }
+                        })?;
 
-                        let mut add = |factors: Vec<FactorInstance>| {
-                            factors.into_iter().for_each(|f| {
-                                let factor_source_id = f.factor_source_id;
-                                use_factor_in_tx(&factor_source_id, &transaction.intent_hash);
-                            })
-                        };
-
-                        add(primary_role_matrix.override_factors.clone());
-                        add(primary_role_matrix.threshold_factors.clone());
+                        for f in primary_role_matrix
+                            .override_factors
+                            .iter()
+                            .chain(&primary_role_matrix.threshold_factors)
+                        {
+                            use_factor_in_tx(&f.factor_source_id, &transaction.intent_hash)?;
+                        }
 
                         let builder = SignaturesBuilderLevel2::new_securified(
                             address.clone(),
@@ -104,7 +157,7 @@ impl SignaturesBuilderLevel0 {
                     EntitySecurityState::Unsecured(uec) => {
                         let factor_instance = uec;
                         let factor_source_id = factor_instance.factor_source_id;
-                        use_factor_in_tx(&factor_source_id, &transaction.intent_hash);
+                        use_factor_in_tx(&factor_source_id, &transaction.intent_hash)?;
 
                         let builder = SignaturesBuilderLevel2::new_unsecurified(
                             address.clone(),
@@ -120,55 +173,280 @@ impl SignaturesBuilderLevel0 {
             );
         }
 
-        let factors_of_kind = used_factor_sources
-            .into_iter()
-            .into_grouping_map_by(|x| x.kind())
-            .collect::<IndexSet<FactorSource>>();
-
-        let mut factors_of_kind = factors_of_kind
-            .into_iter()
-            .map(|(k, v)| (k, v.into_iter().sorted().collect::<IndexSet<_>>()))
-            .collect::<IndexMap<FactorSourceKind, IndexSet<FactorSource>>>();
+        let factors_of_kind = options
+            .ordering
+            .factors_of_kind(used_factor_sources.into_iter().collect());
 
-        factors_of_kind.sort_keys();
-
-        let self_ = Self {
+        Ok(Self {
             user,
+            options,
             builders_level_0: builders_level_0.into(),
             factors_of_kind,
             factor_to_payloads,
+            skipped_factor_sources: IndexMap::new().into(),
+            neglected_factor_sources: IndexMap::new().into(),
+        })
+    }
+}
+
+impl SignaturesBuilderLevel0 {
+    /// Exports the state of this signing process, see `restore`.
+    pub fn snapshot(&self) -> SigningSessionSnapshot {
+        SigningSessionSnapshot {
+            transactions: self
+                .builders_level_0
+                .read()
+                .unwrap()
+                .values()
+                .map(|builders_level_1| builders_level_1.snapshot())
+                .collect(),
+            skipped_factor_sources: self
+                .skipped_factor_sources
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(factor_source_id, reason)| SkippedFactorSource {
+                    factor_source_id: *factor_source_id,
+                    reason: *reason,
+                })
+                .collect(),
+            neglected_factor_sources: self
+                .neglected_factor_sources
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(factor_source_id, reason)| NeglectedFactorSource {
+                    factor_source_id: *factor_source_id,
+                    reason: reason.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Recreates a signing process from a `snapshot` exported by `snapshot`,
+    /// `all_factor_sources_in_profile` and `transactions` MUST be the same as
+    /// the ones the snapshotted signing process was created with.
+    ///
+    /// Calling `sign` continues with the next factor source not evaluated.
+    ///
+    /// Fails if `snapshot` contains a transaction, entity or factor source
+    /// not being signed (with), or if the factor sources skipped or neglected
+    /// for the entities disagree with the ones of the whole process.
+    pub fn restore(
+        user: SigningUser,
+        all_factor_sources_in_profile: IndexSet<FactorSource>,
+        transactions: IndexSet<TransactionIntent>,
+        options: SigningOptions,
+        snapshot: SigningSessionSnapshot,
+    ) -> Result<Self> {
+        let restored =
+            Self::new_with_options(user, all_factor_sources_in_profile, transactions, options)?;
+        if let Some(factor_source_id) = snapshot
+            .skipped_factor_sources
+            .iter()
+            .map(|s| &s.factor_source_id)
+            .chain(
+                snapshot
+                    .neglected_factor_sources
+                    .iter()
+                    .map(|n| &n.factor_source_id),
+            )
+            .find(|id| !restored.factor_to_payloads.contains_key(id))
+        {
+            return Err(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::FactorSourceNotSignedWith(*factor_source_id),
+            ));
+        }
+        {
+            let builders_level_0 = restored.builders_level_0.read().unwrap();
+            for transaction in snapshot.transactions {
+                builders_level_0
+                    .get(&transaction.intent_hash)
+                    .ok_or_else(|| CommonError::UnknownIntent(transaction.intent_hash.clone()))?
+                    .restore(transaction)?;
+            }
+        }
+        *restored.skipped_factor_sources.write().unwrap() = snapshot
+            .skipped_factor_sources
+            .into_iter()
+            .map(|s| (s.factor_source_id, s.reason))
+            .collect();
+        *restored.neglected_factor_sources.write().unwrap() = snapshot
+            .neglected_factor_sources
+            .into_iter()
+            .map(|n| (n.factor_source_id, n.reason))
+            .collect();
+        {
+            let skipped = restored
+                .skipped_factor_sources
+                .read()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect::<IndexSet<_>>();
+            let neglected = restored
+                .neglected_factor_sources
+                .read()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect::<IndexSet<_>>();
+            restored
+                .builders_level_0
+                .read()
+                .unwrap()
+                .values()
+                .try_for_each(|b| b.validate_restored(&skipped, &neglected))?;
+        }
+        Ok(restored)
+    }
+
+    /// What the user is told when prompted for `factor_source`, e.g. the
+    /// progress of every transaction, fails if `factor_source` is not signed
+    /// with in this signing process.
+    pub fn prompt_context(&self, factor_source: &FactorSource) -> Result<SigningPromptContext> {
+        let all_factor_sources = self.factors_of_kind.values().flatten().collect_vec();
+        let position = all_factor_sources
+            .iter()
+            .position(|f| f.id == factor_source.id)
+            .ok_or(CommonError::UnknownFactorSource(factor_source.id))?;
+        Ok(SigningPromptContext {
+            invalid_tx_if_skipped: self.invalid_if_skip_factor_source(factor_source),
+            transactions: self
+                .builders_level_0
+                .read()
+                .unwrap()
+                .values()
+                .map(|builders_level_1| builders_level_1.prompt_status())
+                .collect(),
+            position,
+            number_of_factor_sources: all_factor_sources.len(),
+        })
+    }
+
+    /// The factor sources `sign` will prompt the user for, in "signing
+    /// order", without prompting the user. Factor sources already evaluated
+    /// are not part of the plan.
+    pub fn plan(&self) -> SigningPlan {
+        let evaluated = self.evaluated_factor_source_ids();
+        let factor_sources = self
+            .factors_of_kind
+            .values()
+            .flatten()
+            .filter(|f| !evaluated.contains(&f.id))
+            .map(|factor_source| {
+                let invalid_tx_if_skipped = self.invalid_if_skip_factor_source(factor_source);
+                let builders_level_0 = self.builders_level_0.read().unwrap();
+                let serves = self
+                    .factor_to_payloads
+                    .get(&factor_source.id)
+                    .unwrap()
+                    .iter()
+                    .map(|intent_hash| ServedTransaction {
+                        intent_hash: intent_hash.clone(),
+                        entities: builders_level_0
+                            .get(intent_hash)
+                            .unwrap()
+                            .entities_referencing_factor_source(&factor_source.id),
+                    })
+                    .collect();
+                PlannedFactorSource {
+                    factor_source_id: factor_source.id,
+                    serves,
+                    invalid_tx_if_skipped,
+                }
+            })
+            .collect();
+        let transactions = self
+            .builders_level_0
+            .read()
+            .unwrap()
+            .values()
+            .map(|builders_level_1| builders_level_1.plan())
+            .collect();
+        SigningPlan {
+            factor_sources,
+            transactions,
+        }
+    }
+
+    /// A cheapest set of the factor sources not yet evaluated - according to
+    /// `costs` - which fulfills the signatures requirement of every entity of
+    /// every transaction which can still succeed, `None` if no such set
+    /// exists. Irrecoverably invalid transactions are disregarded, so that
+    /// one doomed transaction does not stop the others from being optimised.
+    pub fn minimum_cost_factor_sources(
+        &self,
+        costs: &FactorSourceCosts,
+    ) -> Option<IndexSet<FactorSourceID>> {
+        let evaluated = self.evaluated_factor_source_ids();
+        let candidates = self
+            .factors_of_kind
+            .values()
+            .flatten()
+            .filter(|f| !evaluated.contains(&f.id))
+            .cloned()
+            .collect_vec();
+        let requirements = self
+            .builders_level_0
+            .read()
+            .unwrap()
+            .values()
+            .filter(|builders_level_1| !builders_level_1.is_irrecoverably_invalid())
+            .flat_map(|builders_level_1| builders_level_1.remaining_requirements())
+            .collect_vec();
+        minimum_cost_factor_sources(&candidates, &requirements, costs)
+    }
+
+    /// The factor sources not yet evaluated which need not be signed with,
+    /// i.e. those outside `minimum_cost_factor_sources`, empty if there is no
+    /// such minimum cost set.
+    pub fn suggested_factor_sources_to_skip(
+        &self,
+        costs: &FactorSourceCosts,
+    ) -> IndexSet<FactorSourceID> {
+        let Some(minimum) = self.minimum_cost_factor_sources(costs) else {
+            return IndexSet::new();
         };
+        let evaluated = self.evaluated_factor_source_ids();
+        self.factors_of_kind
+            .values()
+            .flatten()
+            .map(|f| f.id)
+            .filter(|id| !evaluated.contains(id) && !minimum.contains(id))
+            .collect()
+    }
 
-        // println!("\n\nuser: {:?}", &self_.user);
-        // {
-        //     println!(
-        //         "\n\nbuilders_level_0: {:?}",
-        //         &self_
-        //             .builders_level_0
-        //             .borrow()
-        //             .iter()
-        //             .map(|(k, v)| format!("k: {:?} => v: {:?}", &k, &v))
-        //             .join("\n")
-        //     );
-        // }
-        // println!(
-        //     "\n\nfactors_of_kind: {:?}",
-        //     &self_
-        //         .factors_of_kind
-        //         .iter()
-        //         .map(|(k, v)| format!("k: {:?} => v: {:?}", &k, &v))
-        //         .join("\n")
-        // );
-        // println!(
-        //     "\n\nfactor_to_payloads: {:?}",
-        //     &self_
-        //         .factor_to_payloads
-        //         .iter()
-        //         .map(|(k, v)| format!("k: {:?} => v: {:?}", &k, &v))
-        //         .join("\n")
-        // );
-
-        self_
+    /// Returns `true` if `SigningOptions::skip_outside_minimum_cost_set` is
+    /// set and `factor_source` is outside the minimum cost set.
+    fn is_suggested_to_skip(&self, factor_source: &FactorSource) -> bool {
+        self.options
+            .skip_outside_minimum_cost_set
+            .as_ref()
+            .is_some_and(|costs| {
+                self.suggested_factor_sources_to_skip(costs)
+                    .contains(&factor_source.id)
+            })
+    }
+
+    /// IDs of the factor sources which have been skipped, neglected or signed
+    /// with, in "signing order".
+    fn evaluated_factor_source_ids(&self) -> IndexSet<FactorSourceID> {
+        let skipped = self.skipped_factor_sources.read().unwrap();
+        let neglected = self.neglected_factor_sources.read().unwrap();
+        let signed_with = self
+            .signatures()
+            .iter()
+            .map(|s| *s.factor_source_id())
+            .collect::<HashSet<_>>();
+        self.factors_of_kind
+            .values()
+            .flatten()
+            .map(|f| f.id)
+            .filter(|id| {
+                skipped.contains_key(id) || neglected.contains_key(id) || signed_with.contains(id)
+            })
+            .collect()
     }
 }
 
@@ -179,19 +457,17 @@ impl IsSignaturesBuilder for SignaturesBuilderLevel0 {
         &self,
         factor_source: &FactorSource,
     ) -> IndexSet<Self::InvalidIfSkipped> {
-        let tx_ids = self
-            .factor_to_payloads
-            .get(&factor_source.id)
-            .expect(&format!(
-                "Nil found when unwrapping factor_to_payloads by factor_source: '{:?}'",
-                &factor_source.id
-            ));
+        let Some(tx_ids) = self.factor_to_payloads.get(&factor_source.id) else {
+            // Not used by any transaction, skipping it invalidates none.
+            return IndexSet::new();
+        };
 
         tx_ids
-            .into_iter()
+            .iter()
             .flat_map(|txid| {
                 self.builders_level_0
-                    .borrow()
+                    .read()
+                    .unwrap()
                     .get(txid)
                     .unwrap()
                     .invalid_if_skip_factor_source(factor_source)
@@ -199,99 +475,758 @@ impl IsSignaturesBuilder for SignaturesBuilderLevel0 {
             .collect::<IndexSet<_>>()
     }
 
-    fn skip_factor_sources(&self, factor_source: &FactorSource) {
+    fn skip_factor_sources(&self, factor_source: &FactorSource, reason: SkipReason) {
+        let tx_ids = self.factor_to_payloads.get(&factor_source.id).unwrap();
+
+        let mut builders_level_0 = self.builders_level_0.write().unwrap();
+
+        tx_ids.iter().for_each(|txid| {
+            builders_level_0
+                .get_mut(txid)
+                .unwrap()
+                .skip_factor_sources(factor_source, reason)
+        });
+
+        drop(builders_level_0);
+
+        self.skipped_factor_sources
+            .write()
+            .unwrap()
+            .insert(factor_source.id, reason);
+    }
+
+    fn neglect_factor_source(&self, factor_source: &FactorSource, reason: NeglectReason) {
         let tx_ids = self.factor_to_payloads.get(&factor_source.id).unwrap();
 
-        let mut builders_level_0 = self.builders_level_0.borrow_mut();
+        let mut builders_level_0 = self.builders_level_0.write().unwrap();
 
-        tx_ids.into_iter().for_each(|txid| {
+        tx_ids.iter().for_each(|txid| {
             builders_level_0
                 .get_mut(txid)
                 .unwrap()
-                .skip_factor_sources(factor_source)
+                .neglect_factor_source(factor_source, reason.clone())
         });
 
         drop(builders_level_0);
+
+        self.neglected_factor_sources
+            .write()
+            .unwrap()
+            .insert(factor_source.id, reason);
     }
 
     fn append_signature(&self, signature: SignatureByOwnedFactorForPayload) {
-        let mut builders_level_0 = self.builders_level_0.borrow_mut();
+        let mut builders_level_0 = self.builders_level_0.write().unwrap();
 
         builders_level_0
             .get_mut(&signature.intent_hash)
             .unwrap()
-            .append_signature(signature.clone());
+            .append_signature(signature);
 
         drop(builders_level_0);
     }
 
     fn signatures(&self) -> IndexSet<SignatureByOwnedFactorForPayload> {
         self.builders_level_0
-            .borrow()
+            .read()
+            .unwrap()
             .values()
-            .into_iter()
             .flat_map(|builders_level_1| builders_level_1.signatures())
             .collect()
     }
 
     fn has_fulfilled_signatures_requirement(&self) -> bool {
         self.builders_level_0
-            .borrow()
+            .read()
+            .unwrap()
             .values()
             .all(|builders_level_1| builders_level_1.has_fulfilled_signatures_requirement())
     }
 }
 
 impl SignaturesBuilderLevel0 {
-    async fn sign_with(&self, factor_source: &FactorSource) {
-        let mut signatures = IndexSet::<SignatureByOwnedFactorForPayload>::new();
-        {
-            let factor_source_id = &factor_source.id;
-
-            let builders_level_0 = self.builders_level_0.borrow();
-            for intent_hash in self
-                .factor_to_payloads
+    /// Signs all payloads `factor_source` is required for, without appending
+    /// the signatures, fails if signing any of the payloads fails, or if the
+    /// `SigningOptions::signer` did not return exactly one signature per
+    /// owned factor instance of the payload.
+    async fn signatures_of(
+        &self,
+        factor_source: &FactorSource,
+    ) -> Result<IndexSet<SignatureByOwnedFactorForPayload>> {
+        let factor_source_id = &factor_source.id;
+        let owned_instances_per_payload = {
+            let builders_level_0 = self.builders_level_0.read().unwrap();
+            self.factor_to_payloads
                 .get(factor_source_id)
                 .unwrap()
                 .iter()
-            {
-                let signatures_builder = builders_level_0.get(intent_hash).unwrap();
-                let owned_instances =
-                    signatures_builder.owned_instances_of_factor_source(factor_source_id);
-                let sigs = factor_source.batch_sign(intent_hash, owned_instances).await;
-                signatures.extend(sigs);
+                .map(|intent_hash| {
+                    let signatures_builder = builders_level_0.get(intent_hash).unwrap();
+                    signatures_builder
+                        .owned_instances_of_factor_source(factor_source_id)
+                        .map(|owned_instances| (intent_hash.clone(), owned_instances))
+                })
+                .collect::<Result<IndexMap<IntentHash, IndexSet<OwnedFactorInstance>>>>()?
+        };
+
+        let mut signatures = IndexSet::<SignatureByOwnedFactorForPayload>::new();
+        for (intent_hash, owned_instances) in owned_instances_per_payload {
+            let sigs = self
+                .options
+                .signer
+                .batch_sign(factor_source, &intent_hash, owned_instances.clone())
+                .await?;
+            let signed_instances = sigs
+                .iter()
+                .filter(|s| s.intent_hash == intent_hash)
+                .map(|s| s.owned_factor_instance.clone())
+                .collect::<IndexSet<_>>();
+            if sigs.len() != owned_instances.len() || signed_instances != owned_instances {
+                warn!(factor_source_id = ?factor_source_id, ?intent_hash, "Unexpected signatures");
+                return Err(CommonError::UnexpectedSignatures {
+                    factor_source_id: *factor_source_id,
+                    intent_hash,
+                });
+            }
+            signatures.extend(sigs);
+        }
+        Ok(signatures)
+    }
+
+    /// Signs with `factor_source` like `signatures_of`, returns `None` if it
+    /// did not sign within the `SigningTimeouts` of its kind.
+    async fn attempt_signing(
+        &self,
+        factor_source: &FactorSource,
+    ) -> Option<Result<IndexSet<SignatureByOwnedFactorForPayload>>> {
+        within(
+            self.options.timeouts.signing_for(factor_source.kind()),
+            self.signatures_of(factor_source),
+        )
+        .await
+    }
+
+    /// Signs with `factor_source`, see `handle_signing_result`.
+    async fn sign_with_retries(&self, factor_source: &FactorSource) -> ControlFlow<Interruption> {
+        let result = self.attempt_signing(factor_source).await;
+        self.handle_signing_result(factor_source, result).await
+    }
+
+    /// Appends the signatures if signing with `factor_source` was successful,
+    /// otherwise asks the user if she wants to retry - as long as the
+    /// `RetryPolicy` allows it - skip, in which case the factor source is
+    /// neglected, or cancel, in which case `ControlFlow::Break` is returned.
+    /// After the last attempt the user is still asked, so that she can
+    /// cancel, answering retry then neglects the factor source.
+    ///
+    /// An attempt which timed out (`None`) neglects the factor source right
+    /// away, as does the user not answering in time.
+    async fn handle_signing_result(
+        &self,
+        factor_source: &FactorSource,
+        first_attempt: Option<Result<IndexSet<SignatureByOwnedFactorForPayload>>>,
+    ) -> ControlFlow<Interruption> {
+        let mut attempts = 1;
+        let mut result = first_attempt;
+        loop {
+            let failure = match result {
+                None => return self.neglect_and_notify(factor_source, NeglectReason::TimedOut),
+                Some(Ok(signatures)) => {
+                    self.append_signatures_and_notify(factor_source, signatures);
+                    return ControlFlow::Continue(());
+                }
+                Some(Err(failure)) => failure,
+            };
+            let can_retry = self.options.retry_policy.can_retry_after(attempts);
+            let invalid_tx_if_skipped = self.invalid_if_skip_factor_source(factor_source);
+            let Some(input) = within(
+                self.options
+                    .timeouts
+                    .user_decision_for(factor_source.kind()),
+                self.user.retry_skip_or_cancel(
+                    factor_source,
+                    failure.clone(),
+                    invalid_tx_if_skipped,
+                    can_retry,
+                ),
+            )
+            .await
+            else {
+                return self.neglect_and_notify(factor_source, NeglectReason::TimedOut);
+            };
+            match input {
+                SigningRetryInput::Retry if can_retry => {
+                    attempts += 1;
+                    result = self.attempt_signing(factor_source).await;
+                }
+                SigningRetryInput::Retry | SigningRetryInput::Skip => {
+                    return self.neglect_and_notify(factor_source, NeglectReason::Failure(failure));
+                }
+                SigningRetryInput::Cancel => return ControlFlow::Break(Interruption::Cancelled),
+            }
+        }
+    }
+
+    /// The reason to skip `factor_source` without prompting the user, if any:
+    /// if all transactions have already fulfilled their signatures
+    /// requirement and `SigningOptions::after_all_transactions_fulfilled`
+    /// says to skip the remaining factor sources, if all transactions it would
+    /// sign are irrecoverably invalid, or if it is suggested to skip it - see
+    /// `SigningOptions::skip_outside_minimum_cost_set`.
+    fn automatic_skip_reason(&self, factor_source: &FactorSource) -> Option<SkipReason> {
+        if self.has_fulfilled_signatures_requirement()
+            && self.options.after_all_transactions_fulfilled
+                == AfterAllTransactionsFulfilled::SkipRemaining
+        {
+            return Some(SkipReason::AllTransactionsFulfilled);
+        }
+        if self.only_serves_irrecoverably_invalid_transactions(factor_source) {
+            return Some(SkipReason::OnlyServesInvalidTransactions);
+        }
+        if self.is_suggested_to_skip(factor_source) {
+            return Some(SkipReason::OutsideMinimumCostSet);
+        }
+        None
+    }
+
+    /// Returns `true` if every transaction `factor_source` would sign is
+    /// irrecoverably invalid, i.e. signing with it is pointless.
+    fn only_serves_irrecoverably_invalid_transactions(&self, factor_source: &FactorSource) -> bool {
+        let builders_level_0 = self.builders_level_0.read().unwrap();
+        self.factor_to_payloads
+            .get(&factor_source.id)
+            .unwrap()
+            .iter()
+            .all(|intent_hash| {
+                builders_level_0
+                    .get(intent_hash)
+                    .unwrap()
+                    .is_irrecoverably_invalid()
+            })
+    }
+
+    /// Asks the user if she wants to sign with or skip `factor_source`, if
+    /// all transactions have already fulfilled their signatures requirement
+    /// `SigningOptions::after_all_transactions_fulfilled` decides how.
+    ///
+    /// Continues with `None` if the user did not answer within the
+    /// `SigningTimeouts` of the kind of `factor_source`.
+    async fn prompt_sign_or_skip(
+        &self,
+        factor_source: &FactorSource,
+    ) -> ControlFlow<Interruption, Option<SigningUserInput>> {
+        let timeout = self
+            .options
+            .timeouts
+            .user_decision_for(factor_source.kind());
+        if self.has_fulfilled_signatures_requirement()
+            && self.options.after_all_transactions_fulfilled
+                == AfterAllTransactionsFulfilled::PromptForExtraSignatures
+        {
+            self.notify(SigningEvent::FactorSourcePrompted {
+                factor_source_id: factor_source.id,
+                invalid_tx_if_skipped: IndexSet::new(),
+            });
+            debug!(factor_source_id = ?factor_source.id, "Prompting for extra signature");
+            let input = within(timeout, self.user.sign_extra_or_skip(factor_source)).await;
+            debug!(factor_source_id = ?factor_source.id, ?input, "User decided");
+            return ControlFlow::Continue(input);
+        }
+        let context = match self.prompt_context(factor_source) {
+            Ok(context) => context,
+            Err(error) => return ControlFlow::Break(Interruption::Aborted(error)),
+        };
+        self.notify(SigningEvent::FactorSourcePrompted {
+            factor_source_id: factor_source.id,
+            invalid_tx_if_skipped: context.invalid_tx_if_skipped.clone(),
+        });
+        debug!(
+            factor_source_id = ?factor_source.id,
+            invalid_tx_if_skipped = ?context.invalid_tx_if_skipped,
+            "Prompting to sign or skip"
+        );
+        let input = within(
+            timeout,
+            self.user.sign_or_skip_in_context(factor_source, context),
+        )
+        .await;
+        debug!(factor_source_id = ?factor_source.id, ?input, "User decided");
+        ControlFlow::Continue(input)
+    }
+
+    /// Prompts the user for each of the `factor_sources` (all of the same
+    /// kind) - unless skipped automatically - and signs with each one she
+    /// chooses to sign with right away.
+    async fn sign_with_factors_of_kind_sequentially(
+        &self,
+        factor_sources: &IndexSet<FactorSource>,
+    ) -> ControlFlow<Interruption> {
+        for (index, factor_source) in factor_sources.iter().enumerate() {
+            if let Some(reason) = self.automatic_skip_reason(factor_source) {
+                self.skip_and_notify(factor_source, reason)?;
+                continue;
+            }
+            match self.prompt_sign_or_skip(factor_source).await? {
+                Some(SigningUserInput::Sign) => self.sign_with_retries(factor_source).await?,
+                Some(SigningUserInput::Skip) => {
+                    self.skip_and_notify(factor_source, SkipReason::User)?
+                }
+                Some(SigningUserInput::SkipAllOfKind) => {
+                    return self.skip_remaining_of_kind_and_notify(factor_sources, index)
+                }
+                Some(SigningUserInput::Cancel) => {
+                    return ControlFlow::Break(Interruption::Cancelled)
+                }
+                None => self.neglect_and_notify(factor_source, NeglectReason::TimedOut)?,
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Skips the factor source at `index` of `factor_sources` (all of the
+    /// same kind) and every one after it, since the user chose to skip every
+    /// factor source of the kind. Factor sources which would have been
+    /// skipped automatically keep their automatic `SkipReason`.
+    fn skip_remaining_of_kind_and_notify(
+        &self,
+        factor_sources: &IndexSet<FactorSource>,
+        index: usize,
+    ) -> ControlFlow<Interruption> {
+        debug!(kind = ?factor_sources[index].kind(), "Skipping all remaining of kind");
+        self.skip_and_notify(&factor_sources[index], SkipReason::User)?;
+        for factor_source in factor_sources.iter().skip(index + 1) {
+            let reason = self
+                .automatic_skip_reason(factor_source)
+                .unwrap_or(SkipReason::User);
+            self.skip_and_notify(factor_source, reason)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Prompts the user for each of the `factor_sources` (all of the same
+    /// kind) - unless skipped automatically - first, and then signs with all
+    /// the ones she chose to sign with concurrently, at most `limit` at a
+    /// time.
+    ///
+    /// The user is prompted for all of them before any of them signs, so the
+    /// prompt context - and the automatic skipping - of each factor source is
+    /// computed from the state before signing with this kind. The ones she
+    /// already chose to sign with count as if they will sign, i.e. the
+    /// `invalid_tx_if_skipped` of a later factor source does not include the
+    /// transactions which only become invalid if one of them fails, and a
+    /// later factor source is prompted for even if they will fulfill every
+    /// transaction.
+    async fn sign_with_factors_of_kind_concurrently(
+        &self,
+        factor_sources: &IndexSet<FactorSource>,
+        limit: usize,
+    ) -> ControlFlow<Interruption> {
+        let mut to_sign_with = Vec::<&FactorSource>::new();
+        for (index, factor_source) in factor_sources.iter().enumerate() {
+            if let Some(reason) = self.automatic_skip_reason(factor_source) {
+                self.skip_and_notify(factor_source, reason)?;
+                continue;
+            }
+            match self.prompt_sign_or_skip(factor_source).await? {
+                Some(SigningUserInput::Sign) => to_sign_with.push(factor_source),
+                Some(SigningUserInput::Skip) => {
+                    self.skip_and_notify(factor_source, SkipReason::User)?
+                }
+                Some(SigningUserInput::SkipAllOfKind) => {
+                    self.skip_remaining_of_kind_and_notify(factor_sources, index)?;
+                    break;
+                }
+                Some(SigningUserInput::Cancel) => {
+                    return ControlFlow::Break(Interruption::Cancelled)
+                }
+                None => self.neglect_and_notify(factor_source, NeglectReason::TimedOut)?,
             }
         }
-        signatures
+        self.sign_with_all_concurrently(to_sign_with, limit).await
+    }
+
+    /// Signs with all of `to_sign_with` concurrently, at most `limit` at a
+    /// time. The signatures are appended - and failures handled - in signing
+    /// order once all factor sources have signed.
+    async fn sign_with_all_concurrently(
+        &self,
+        to_sign_with: Vec<&FactorSource>,
+        limit: usize,
+    ) -> ControlFlow<Interruption> {
+        // Futures are lazy, so creating them all up front does not start
+        // signing, `buffered` polls at most `limit` of them at a time.
+        let signing = to_sign_with
+            .iter()
+            .map(|factor_source| self.attempt_signing(factor_source))
+            .collect_vec();
+        let results = stream::iter(signing)
+            .buffered(limit)
+            .collect::<Vec<_>>()
+            .await;
+
+        for (factor_source, result) in to_sign_with.into_iter().zip(results) {
+            self.handle_signing_result(factor_source, result).await?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Asks the user once which of the `factor_sources` of `kind` she wants
+    /// to sign with.
+    ///
+    /// Returns `None` if the user did not answer within the `SigningTimeouts`
+    /// of `kind`.
+    async fn prompt_sign_or_skip_kind(
+        &self,
+        kind: FactorSourceKind,
+        factor_sources: &[&FactorSource],
+    ) -> Option<SigningKindUserInput> {
+        let invalid_tx_if_skipped_per_factor_source = factor_sources
+            .iter()
+            .map(|factor_source| {
+                let invalid_tx_if_skipped = self.invalid_if_skip_factor_source(factor_source);
+                self.notify(SigningEvent::FactorSourcePrompted {
+                    factor_source_id: factor_source.id,
+                    invalid_tx_if_skipped: invalid_tx_if_skipped.clone(),
+                });
+                ((*factor_source).clone(), invalid_tx_if_skipped)
+            })
+            .collect::<IndexMap<_, _>>();
+        let factor_source_ids = factor_sources.iter().map(|f| f.id).collect_vec();
+        debug!(?kind, ?factor_source_ids, "Prompting to sign or skip kind");
+        let input = within(
+            self.options.timeouts.user_decision_for(kind),
+            self.user
+                .sign_or_skip_kind(kind, invalid_tx_if_skipped_per_factor_source),
+        )
+        .await;
+        debug!(?kind, ?input, "User decided");
+        input
+    }
+
+    /// Skips the `factor_sources` of `kind` which are skipped automatically,
+    /// prompts the user once for the others, then skips the ones she chose to
+    /// skip and signs with the rest - concurrently if configured so for
+    /// `kind`.
+    ///
+    /// If all transactions have already fulfilled their signatures
+    /// requirement and the user is to be prompted for extra signatures, she
+    /// is prompted for each factor source instead, just like with
+    /// `PromptGranularity::PerFactorSource`.
+    async fn sign_with_factors_of_kind_prompting_once(
+        &self,
+        kind: FactorSourceKind,
+        factor_sources: &IndexSet<FactorSource>,
+    ) -> ControlFlow<Interruption> {
+        if self.has_fulfilled_signatures_requirement()
+            && self.options.after_all_transactions_fulfilled
+                == AfterAllTransactionsFulfilled::PromptForExtraSignatures
+        {
+            return match self.options.concurrency.limit_for_kind(kind) {
+                Some(limit) => {
+                    self.sign_with_factors_of_kind_concurrently(factor_sources, limit)
+                        .await
+                }
+                None => {
+                    self.sign_with_factors_of_kind_sequentially(factor_sources)
+                        .await
+                }
+            };
+        }
+        let mut to_prompt = Vec::<&FactorSource>::new();
+        for factor_source in factor_sources.iter() {
+            match self.automatic_skip_reason(factor_source) {
+                Some(reason) => self.skip_and_notify(factor_source, reason)?,
+                None => to_prompt.push(factor_source),
+            }
+        }
+        if to_prompt.is_empty() {
+            return ControlFlow::Continue(());
+        }
+        let ids_to_sign_with = match self.prompt_sign_or_skip_kind(kind, &to_prompt).await {
+            Some(SigningKindUserInput::SignWith(ids_to_sign_with)) => ids_to_sign_with,
+            Some(SigningKindUserInput::Cancel) => {
+                return ControlFlow::Break(Interruption::Cancelled)
+            }
+            None => {
+                for factor_source in to_prompt {
+                    self.neglect_and_notify(factor_source, NeglectReason::TimedOut)?;
+                }
+                return ControlFlow::Continue(());
+            }
+        };
+        let (to_sign_with, to_skip): (Vec<&FactorSource>, Vec<&FactorSource>) = to_prompt
             .into_iter()
-            .for_each(|s| self.append_signature(s));
+            .partition(|f| ids_to_sign_with.contains(&f.id));
+
+        for factor_source in to_skip {
+            self.skip_and_notify(factor_source, SkipReason::User)?;
+        }
+
+        match self.options.concurrency.limit_for_kind(kind) {
+            Some(limit) => self.sign_with_all_concurrently(to_sign_with, limit).await,
+            None => {
+                for factor_source in to_sign_with {
+                    self.sign_with_retries(factor_source).await?;
+                }
+                ControlFlow::Continue(())
+            }
+        }
     }
 
-    pub async fn sign(&self) -> Signatures {
+    fn transaction_statuses(&self) -> IndexMap<IntentHash, TransactionSigningStatus> {
+        self.builders_level_0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(intent_hash, builders_level_1)| (intent_hash.clone(), builders_level_1.status()))
+            .collect()
+    }
+
+    fn notify(&self, event: SigningEvent) {
+        if let Some(observer) = &self.options.observer {
+            observer.on_event(event)
+        }
+    }
+
+    /// Performs `update`, then notifies the observer about `event` followed by
+    /// any transaction which became fulfilled or invalid due to the update.
+    fn update_and_notify(&self, event: SigningEvent, update: impl FnOnce()) {
+        let statuses_before = self.transaction_statuses();
+        update();
+        self.notify(event);
+
+        let statuses_after = self.transaction_statuses();
+        let builders_level_0 = self.builders_level_0.read().unwrap();
+        for (intent_hash, status) in statuses_after {
+            if statuses_before.get(&intent_hash) == Some(&status) {
+                continue;
+            }
+            let builders_level_1 = builders_level_0.get(&intent_hash).unwrap();
+            match status {
+                TransactionSigningStatus::Fulfilled => {
+                    info!(?intent_hash, "Transaction fulfilled");
+                    self.notify(SigningEvent::TransactionFulfilled {
+                        intent_hash,
+                        entities: builders_level_1.entities(),
+                    })
+                }
+                TransactionSigningStatus::Invalid => {
+                    warn!(?intent_hash, "Transaction became invalid");
+                    self.notify(SigningEvent::TransactionBecameInvalid {
+                        intent_hash,
+                        entities_which_failed_auth: builders_level_1
+                            .entities_which_are_irrecoverably_invalid(),
+                    })
+                }
+                TransactionSigningStatus::Pending => {}
+            }
+        }
+    }
+
+    fn append_signatures_and_notify(
+        &self,
+        factor_source: &FactorSource,
+        signatures: IndexSet<SignatureByOwnedFactorForPayload>,
+    ) {
+        debug!(
+            factor_source_id = ?factor_source.id,
+            signatures = signatures.len(),
+            "Signed"
+        );
+        self.update_and_notify(
+            SigningEvent::SignedWith {
+                factor_source_id: factor_source.id,
+                signatures: signatures.clone(),
+            },
+            || {
+                signatures
+                    .into_iter()
+                    .for_each(|s| self.append_signature(s))
+            },
+        )
+    }
+
+    /// Skips `factor_source`, see `abort_if_any_transaction_is_invalid`.
+    fn skip_and_notify(
+        &self,
+        factor_source: &FactorSource,
+        reason: SkipReason,
+    ) -> ControlFlow<Interruption> {
+        debug!(factor_source_id = ?factor_source.id, ?reason, "Skipped");
+        self.update_and_notify(
+            SigningEvent::Skipped {
+                factor_source_id: factor_source.id,
+                invalid_tx_if_skipped: self.invalid_if_skip_factor_source(factor_source),
+                reason,
+            },
+            || self.skip_factor_sources(factor_source, reason),
+        );
+        self.abort_if_any_transaction_is_invalid()
+    }
+
+    /// Neglects `factor_source`, see `abort_if_any_transaction_is_invalid`.
+    fn neglect_and_notify(
+        &self,
+        factor_source: &FactorSource,
+        reason: NeglectReason,
+    ) -> ControlFlow<Interruption> {
+        warn!(factor_source_id = ?factor_source.id, ?reason, "Neglected");
+        self.update_and_notify(
+            SigningEvent::Neglected {
+                factor_source_id: factor_source.id,
+                reason: reason.clone(),
+            },
+            || self.neglect_factor_source(factor_source, reason),
+        );
+        self.abort_if_any_transaction_is_invalid()
+    }
+
+    /// In `BatchSigningMode::AllOrNothing` returns `ControlFlow::Break` with
+    /// an error naming the first irrecoverably invalid transaction, if any.
+    fn abort_if_any_transaction_is_invalid(&self) -> ControlFlow<Interruption> {
+        if self.options.batch_mode != BatchSigningMode::AllOrNothing {
+            return ControlFlow::Continue(());
+        }
+        let builders_level_0 = self.builders_level_0.read().unwrap();
+        match builders_level_0
+            .values()
+            .find(|builders_level_1| builders_level_1.is_irrecoverably_invalid())
+        {
+            Some(invalid) => {
+                ControlFlow::Break(Interruption::Aborted(CommonError::TransactionInvalid {
+                    intent_hash: invalid.intent_hash.clone(),
+                    entities_which_failed_auth: invalid.entities_which_are_irrecoverably_invalid(),
+                }))
+            }
+            None => ControlFlow::Continue(()),
+        }
+    }
+
+    /// Partitions the transactions into successful and failed ones, based on
+    /// the signatures collected and factor sources skipped so far.
+    fn outcome(&self) -> SignaturesOutcome {
+        // Entities left unsigned only because factor sources were skipped as
+        // they were no longer needed did not fail auth, the culprits did.
+        // Skipping factor sources outside the minimum cost set is a choice,
+        // just like the user skipping, so it is blamed.
+        let not_to_blame = self
+            .skipped_factor_sources
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, reason)| {
+                matches!(
+                    reason,
+                    SkipReason::OnlyServesInvalidTransactions
+                        | SkipReason::AllTransactionsFulfilled
+                )
+            })
+            .map(|(id, _)| *id)
+            .collect::<IndexSet<_>>();
+        let builders_level_0 = self.builders_level_0.read().unwrap();
+        let mut successful_transactions = Vec::<SignedTransaction>::new();
+        let mut failed_transactions = Vec::<FailedTransaction>::new();
+        for (intent_hash, builders_level_1) in builders_level_0.iter() {
+            if builders_level_1.has_fulfilled_signatures_requirement() {
+                successful_transactions.push(SignedTransaction::new(
+                    intent_hash.clone(),
+                    builders_level_1.signatures(),
+                ));
+            } else {
+                failed_transactions.push(FailedTransaction::new(
+                    intent_hash.clone(),
+                    builders_level_1.entities_which_failed_auth_disregarding(&not_to_blame),
+                ));
+            }
+        }
+        SignaturesOutcome {
+            successful_transactions,
+            failed_transactions,
+            skipped_factor_sources: self.skipped_factor_sources.read().unwrap().clone(),
+            neglected_factor_sources: self.neglected_factor_sources.read().unwrap().clone(),
+        }
+    }
+
+    /// Prompts the user for every factor source, sorted by "signing order",
+    /// to either sign or skip it. If the user at any point cancels, no more
+    /// factor sources are prompted for and `SigningOutcome::Cancelled` is
+    /// returned.
+    ///
+    /// If `SigningOptions::prompt_granularity` is `PerFactorSourceKind` the
+    /// user is prompted once per kind instead of once per factor source.
+    ///
+    /// Factor sources of kinds configured for concurrent signing in
+    /// `SigningOptions::concurrency` are signed with concurrently, after
+    /// the user has been prompted for all of them.
+    ///
+    /// If signing with a factor source fails - and the user does not retry -
+    /// it is neglected, which affects which transactions would be invalid if
+    /// subsequent factor sources were skipped.
+    ///
+    /// Factor sources already evaluated, e.g. before a `snapshot` this
+    /// signing process was restored from, are not prompted for again.
+    ///
+    /// In `BatchSigningMode::AllOrNothing` signing is aborted - with a
+    /// `CommonError::TransactionInvalid` error - as soon as any transaction
+    /// becomes invalid.
+    #[tracing::instrument(
+        name = "signing_session",
+        skip_all,
+        fields(transactions = self.builders_level_0.read().unwrap().len())
+    )]
+    pub async fn sign(&self) -> Result<SigningOutcome> {
+        info!("Signing session started");
+        if let ControlFlow::Break(Interruption::Aborted(error)) =
+            self.abort_if_any_transaction_is_invalid()
+        {
+            return Err(error);
+        }
+        let evaluated = self.evaluated_factor_source_ids();
         let factors_of_kind = self.factors_of_kind.clone();
         for (kind, factor_sources) in factors_of_kind.into_iter() {
-            for factor_source in factor_sources.iter() {
-                assert_eq!(factor_source.kind(), kind);
+            assert!(factor_sources.iter().all(|f| f.kind() == kind));
+            let factor_sources = factor_sources
+                .into_iter()
+                .filter(|f| !evaluated.contains(&f.id))
+                .collect::<IndexSet<_>>();
+            if factor_sources.is_empty() {
+                continue;
+            }
 
-                let invalid_tx_if_skipped = self.invalid_if_skip_factor_source(factor_source);
-                let is_skipping = match self
-                    .user
-                    .sign_or_skip(factor_source, invalid_tx_if_skipped)
-                    .await
-                {
-                    SigningUserInput::Skip => true,
-                    SigningUserInput::Sign => false,
-                };
-                if !is_skipping {
-                    // Should sign
-                    self.sign_with(factor_source).await
-                } else {
-                    self.skip_factor_sources(factor_source)
+            let flow = match (
+                self.options.prompt_granularity,
+                self.options.concurrency.limit_for_kind(kind),
+            ) {
+                (PromptGranularity::PerFactorSourceKind, _) => {
+                    self.sign_with_factors_of_kind_prompting_once(kind, &factor_sources)
+                        .await
+                }
+                (PromptGranularity::PerFactorSource, Some(limit)) => {
+                    self.sign_with_factors_of_kind_concurrently(&factor_sources, limit)
+                        .await
+                }
+                (PromptGranularity::PerFactorSource, None) => {
+                    self.sign_with_factors_of_kind_sequentially(&factor_sources)
+                        .await
+                }
+            };
+            match flow {
+                ControlFlow::Continue(()) => {}
+                ControlFlow::Break(Interruption::Cancelled) => {
+                    return Ok(SigningOutcome::Cancelled)
                 }
+                ControlFlow::Break(Interruption::Aborted(error)) => return Err(error),
             }
         }
-        Signatures {
-            all_signatures: self.signatures().clone(),
-        }
+        Ok(SigningOutcome::Signed(self.outcome()))
+    }
+}
+
+/// Awaits `future`, returns `None` if it did not complete within `duration`,
+/// if any.
+async fn within<T>(duration: Option<Duration>, future: impl Future<Output = T>) -> Option<T> {
+    match duration {
+        Some(duration) => actix_rt::time::timeout(duration, future).await.ok(),
+        None => Some(future.await),
     }
 }
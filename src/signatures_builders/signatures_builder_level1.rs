@@ -1,6 +1,7 @@
-use std::cell::RefCell;
+use std::sync::RwLock;
 
 use crate::prelude::*;
+use tracing::{trace_span, Span};
 
 /// `SignaturesBuilderForTransaction`
 /// Signatures Builder for a Transaction: Aggregates over multiple Entities.
@@ -11,13 +12,13 @@ pub struct SignaturesBuilderLevel1 {
     pub intent_hash: IntentHash,
 
     /// Signature builder for each entity signing this transaction
-    pub builders: RefCell<HashMap<AccountAddressOrIdentityAddress, SignaturesBuilderLevel2>>,
+    pub builders: RwLock<IndexMap<AccountAddressOrIdentityAddress, SignaturesBuilderLevel2>>,
 }
 
 impl SignaturesBuilderLevel1 {
     pub fn new(
         intent_hash: IntentHash,
-        builders: HashMap<AccountAddressOrIdentityAddress, SignaturesBuilderLevel2>,
+        builders: IndexMap<AccountAddressOrIdentityAddress, SignaturesBuilderLevel2>,
     ) -> Self {
         Self {
             intent_hash,
@@ -28,14 +29,174 @@ impl SignaturesBuilderLevel1 {
     pub fn owned_instances_of_factor_source(
         &self,
         factor_source_id: &FactorSourceID,
-    ) -> IndexSet<OwnedFactorInstance> {
+    ) -> Result<IndexSet<OwnedFactorInstance>> {
         self.builders
-            .borrow()
+            .read()
+            .unwrap()
             .values()
-            .into_iter()
+            .filter(|builder| builder.references_factor_source(factor_source_id))
             .map(|builder| builder.owned_instance_of_factor_source(factor_source_id))
             .collect()
     }
+
+    /// Addresses of the entities requiring auth for this transaction which
+    /// reference the factor source.
+    pub fn entities_referencing_factor_source(
+        &self,
+        factor_source_id: &FactorSourceID,
+    ) -> Vec<AccountAddressOrIdentityAddress> {
+        self.builders
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, b)| b.references_factor_source(factor_source_id))
+            .map(|(address, _)| address.clone())
+            .collect()
+    }
+
+    /// What is still needed for each entity which has not yet fulfilled its
+    /// signatures requirement.
+    pub fn remaining_requirements(&self) -> Vec<RemainingSignaturesRequirement> {
+        self.builders
+            .read()
+            .unwrap()
+            .values()
+            .filter_map(|b| b.remaining_requirement())
+            .collect()
+    }
+
+    /// Span of this transaction, entered while updating the builders of its
+    /// entities, so that their events carry the intent hash.
+    fn span(&self) -> Span {
+        trace_span!("transaction", intent_hash = ?self.intent_hash)
+    }
+
+    /// Addresses of all entities requiring auth for this transaction.
+    pub fn entities(&self) -> Vec<AccountAddressOrIdentityAddress> {
+        self.builders.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Addresses of the entities which can no longer fulfill their
+    /// signatures requirement, if non empty this transaction will fail.
+    pub fn entities_which_are_irrecoverably_invalid(&self) -> Vec<AccountAddressOrIdentityAddress> {
+        self.builders
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, b)| b.is_irrecoverably_invalid())
+            .map(|(address, _)| address.clone())
+            .collect()
+    }
+
+    /// Returns `true` if some entity can no longer fulfill its signatures
+    /// requirement, i.e. this transaction is guaranteed to fail, no matter
+    /// which of the remaining factor sources are signed with.
+    pub fn is_irrecoverably_invalid(&self) -> bool {
+        self.builders
+            .read()
+            .unwrap()
+            .values()
+            .any(|b| b.is_irrecoverably_invalid())
+    }
+
+    pub fn prompt_status(&self) -> TransactionPromptStatus {
+        TransactionPromptStatus {
+            intent_hash: self.intent_hash.clone(),
+            status: self.status(),
+            entities: self
+                .builders
+                .read()
+                .unwrap()
+                .values()
+                .map(|b| b.prompt_status())
+                .collect(),
+        }
+    }
+
+    pub fn plan(&self) -> PlannedTransaction {
+        PlannedTransaction {
+            intent_hash: self.intent_hash.clone(),
+            entities: self
+                .builders
+                .read()
+                .unwrap()
+                .values()
+                .map(|b| b.plan())
+                .collect(),
+        }
+    }
+
+    pub fn status(&self) -> TransactionSigningStatus {
+        if self.has_fulfilled_signatures_requirement() {
+            TransactionSigningStatus::Fulfilled
+        } else if !self.is_irrecoverably_invalid() {
+            TransactionSigningStatus::Pending
+        } else {
+            TransactionSigningStatus::Invalid
+        }
+    }
+
+    /// The entities which failed auth, not counting factor sources in
+    /// `not_to_blame` as skipped or neglected, see
+    /// `SignaturesBuilderLevel2::has_failed_auth_disregarding`.
+    pub fn entities_which_failed_auth_disregarding(
+        &self,
+        not_to_blame: &IndexSet<FactorSourceID>,
+    ) -> Vec<AccountAddressOrIdentityAddress> {
+        self.builders
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, b)| b.has_failed_auth_disregarding(not_to_blame))
+            .map(|(address, _)| address.clone())
+            .collect()
+    }
+}
+
+impl SignaturesBuilderLevel1 {
+    pub fn snapshot(&self) -> TransactionSigningSnapshot {
+        TransactionSigningSnapshot {
+            intent_hash: self.intent_hash.clone(),
+            entities: self
+                .builders
+                .read()
+                .unwrap()
+                .values()
+                .map(|builder| builder.snapshot())
+                .collect(),
+        }
+    }
+
+    /// Replaces the state of the builder of each entity in `snapshot`, fails
+    /// if `snapshot` contains an entity not requiring auth for this
+    /// transaction.
+    pub fn restore(&self, snapshot: TransactionSigningSnapshot) -> Result<()> {
+        let builders = self.builders.read().unwrap();
+        for entity in snapshot.entities {
+            builders
+                .get(&entity.address)
+                .ok_or_else(|| CommonError::UnknownEntity {
+                    intent_hash: self.intent_hash.clone(),
+                    address: entity.address.clone(),
+                })?
+                .restore(&self.intent_hash, entity)?;
+        }
+        Ok(())
+    }
+
+    /// Fails if the factor sources skipped or neglected for any entity are
+    /// not the ones of the whole signing process, `skipped` and `neglected`.
+    pub fn validate_restored(
+        &self,
+        skipped: &IndexSet<FactorSourceID>,
+        neglected: &IndexSet<FactorSourceID>,
+    ) -> Result<()> {
+        self.builders
+            .read()
+            .unwrap()
+            .values()
+            .try_for_each(|b| b.validate_restored(skipped, neglected))
+    }
 }
 
 impl IsSignaturesBuilder for SignaturesBuilderLevel1 {
@@ -47,9 +208,10 @@ impl IsSignaturesBuilder for SignaturesBuilderLevel1 {
     ) -> IndexSet<Self::InvalidIfSkipped> {
         let addresses_of_entities_which_would_fail_auth = self
             .builders
-            .borrow()
+            .read()
+            .unwrap()
             .values()
-            .into_iter()
+            .filter(|b| b.references_factor_source(&factor_source.id))
             .flat_map(|b| b.invalid_if_skip_factor_source(factor_source))
             .collect::<Vec<AccountAddressOrIdentityAddress>>();
 
@@ -63,33 +225,48 @@ impl IsSignaturesBuilder for SignaturesBuilderLevel1 {
         }
     }
 
-    fn skip_factor_sources(&self, factor_source: &FactorSource) {
+    fn skip_factor_sources(&self, factor_source: &FactorSource, reason: SkipReason) {
+        let _span = self.span().entered();
         self.builders
-            .borrow_mut()
+            .write()
+            .unwrap()
             .values_mut()
-            .for_each(|b| b.skip_factor_sources(factor_source))
+            .filter(|b| b.references_factor_source(&factor_source.id))
+            .for_each(|b| b.skip_factor_sources(factor_source, reason))
+    }
+
+    fn neglect_factor_source(&self, factor_source: &FactorSource, reason: NeglectReason) {
+        let _span = self.span().entered();
+        self.builders
+            .write()
+            .unwrap()
+            .values_mut()
+            .filter(|b| b.references_factor_source(&factor_source.id))
+            .for_each(|b| b.neglect_factor_source(factor_source, reason.clone()))
     }
 
     fn has_fulfilled_signatures_requirement(&self) -> bool {
         self.builders
-            .borrow()
+            .read()
+            .unwrap()
             .values()
-            .into_iter()
             .all(|b| b.has_fulfilled_signatures_requirement())
     }
 
     fn signatures(&self) -> IndexSet<SignatureByOwnedFactorForPayload> {
         self.builders
-            .borrow()
+            .read()
+            .unwrap()
             .values()
-            .into_iter()
             .flat_map(|b| b.signatures())
             .collect()
     }
 
     fn append_signature(&self, signature: SignatureByOwnedFactorForPayload) {
+        let _span = self.span().entered();
         self.builders
-            .borrow_mut()
+            .write()
+            .unwrap()
             .get_mut(&signature.owned_factor_instance.owner)
             .unwrap()
             .append_signature(signature)
@@ -1,14 +1,16 @@
-use std::cell::RefCell;
+use std::sync::RwLock;
 
 use crate::prelude::*;
+use tracing::trace;
 
 /// `SignaturesBuilderOfEntity`
 /// Signatures Builder for an Entity: Aggregates over multiple factor instances.
 #[derive(Debug)]
 pub struct SignaturesBuilderLevel2 {
     owned_matrix_of_factors: OwnedMatrixOfFactorInstances,
-    pub skipped_factor_source_ids: RefCell<Vec<FactorSourceID>>,
-    pub signatures: RefCell<Vec<SignatureByOwnedFactorForPayload>>,
+    pub skipped_factor_source_ids: RwLock<Vec<FactorSourceID>>,
+    pub neglected_factor_sources: RwLock<IndexMap<FactorSourceID, NeglectReason>>,
+    pub signatures: RwLock<Vec<SignatureByOwnedFactorForPayload>>,
 }
 
 impl SignaturesBuilderLevel2 {
@@ -16,6 +18,7 @@ impl SignaturesBuilderLevel2 {
         Self {
             owned_matrix_of_factors,
             skipped_factor_source_ids: Vec::new().into(),
+            neglected_factor_sources: IndexMap::new().into(),
             signatures: Vec::new().into(),
         }
     }
@@ -35,27 +38,167 @@ impl SignaturesBuilderLevel2 {
         Self::new(OwnedMatrixOfFactorInstances::new(address_of_owner, matrix))
     }
 
+    /// Returns `true` if the factor source is either an override or a
+    /// threshold factor of the matrix of the entity.
+    pub fn references_factor_source(&self, factor_source_id: &FactorSourceID) -> bool {
+        self.is_override_factor(factor_source_id) || self.is_threshold_factor(factor_source_id)
+    }
+
+    /// Returns `Err` if the factor source is neither an override nor a
+    /// threshold factor of the matrix of the entity.
     pub fn owned_instance_of_factor_source(
         &self,
         factor_source_id: &FactorSourceID,
-    ) -> OwnedFactorInstance {
-        let factors = if self.is_override_factor(factor_source_id) {
-            &self.owned_matrix_of_factors.matrix.override_factors
-        } else if self.is_threshold_factor(factor_source_id) {
-            &self.owned_matrix_of_factors.matrix.threshold_factors
-        } else {
-            panic!("MUST be either threshold or override")
-        };
-
-        let instance = factors
-            .into_iter()
+    ) -> Result<OwnedFactorInstance> {
+        let matrix = &self.owned_matrix_of_factors.matrix;
+        let instance = matrix
+            .override_factors
+            .iter()
+            .chain(&matrix.threshold_factors)
             .find(|fi| &fi.factor_source_id == factor_source_id)
-            .unwrap();
+            .ok_or(CommonError::UnknownFactorSource(*factor_source_id))?;
 
-        return OwnedFactorInstance::new(
+        Ok(OwnedFactorInstance::new(
             instance.clone(),
             self.owned_matrix_of_factors.address_of_owner.clone(),
-        );
+        ))
+    }
+}
+impl SignaturesBuilderLevel2 {
+    pub fn snapshot(&self) -> EntitySigningSnapshot {
+        EntitySigningSnapshot {
+            address: self.owned_matrix_of_factors.address_of_owner.clone(),
+            skipped_factor_source_ids: self.skipped_factor_source_ids.read().unwrap().clone(),
+            neglected_factor_sources: self
+                .neglected_factor_sources
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(factor_source_id, reason)| NeglectedFactorSource {
+                    factor_source_id: *factor_source_id,
+                    reason: reason.clone(),
+                })
+                .collect(),
+            signatures: self.signatures.read().unwrap().clone(),
+        }
+    }
+
+    /// Replaces the state of this builder - of an entity requiring auth for
+    /// the transaction `intent_hash` - with the one in `snapshot`, fails if
+    /// `snapshot` contains a factor source which is not a factor of the
+    /// entity, a factor source evaluated more than once or a signature not
+    /// by the owned factor instance of the entity for the transaction.
+    pub fn restore(&self, intent_hash: &IntentHash, snapshot: EntitySigningSnapshot) -> Result<()> {
+        let address = &self.owned_matrix_of_factors.address_of_owner;
+        if let Some(factor_source_id) = snapshot
+            .skipped_factor_source_ids
+            .iter()
+            .chain(
+                snapshot
+                    .neglected_factor_sources
+                    .iter()
+                    .map(|n| &n.factor_source_id),
+            )
+            .chain(snapshot.signatures.iter().map(|s| s.factor_source_id()))
+            .find(|id| !self.references_factor_source(id))
+        {
+            return Err(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::FactorSourceNotOfEntity {
+                    address: address.clone(),
+                    factor_source_id: *factor_source_id,
+                },
+            ));
+        }
+        let mut evaluated = HashSet::<FactorSourceID>::new();
+        if let Some(factor_source_id) = snapshot
+            .skipped_factor_source_ids
+            .iter()
+            .chain(
+                snapshot
+                    .neglected_factor_sources
+                    .iter()
+                    .map(|n| &n.factor_source_id),
+            )
+            .chain(snapshot.signatures.iter().map(|s| s.factor_source_id()))
+            .find(|id| !evaluated.insert(**id))
+        {
+            return Err(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::FactorSourceEvaluatedMoreThanOnce {
+                    address: address.clone(),
+                    factor_source_id: *factor_source_id,
+                },
+            ));
+        }
+        for signature in snapshot.signatures.iter() {
+            let factor_source_id = *signature.factor_source_id();
+            if signature.intent_hash != *intent_hash {
+                return Err(CommonError::InconsistentSnapshot(
+                    InconsistentSnapshotReason::SignatureOfOtherTransaction {
+                        intent_hash: intent_hash.clone(),
+                        address: address.clone(),
+                        factor_source_id,
+                    },
+                ));
+            }
+            if self.owned_instance_of_factor_source(&factor_source_id)?
+                != signature.owned_factor_instance
+            {
+                return Err(CommonError::InconsistentSnapshot(
+                    InconsistentSnapshotReason::SignatureOfOtherFactorInstance {
+                        address: address.clone(),
+                        factor_source_id,
+                    },
+                ));
+            }
+        }
+        *self.skipped_factor_source_ids.write().unwrap() = snapshot.skipped_factor_source_ids;
+        *self.neglected_factor_sources.write().unwrap() = snapshot
+            .neglected_factor_sources
+            .into_iter()
+            .map(|n| (n.factor_source_id, n.reason))
+            .collect();
+        *self.signatures.write().unwrap() = snapshot.signatures;
+        Ok(())
+    }
+
+    /// Fails if the factor sources of the entity skipped or neglected for it
+    /// are not exactly the ones of the whole signing process, `skipped` and
+    /// `neglected`, i.e. if they disagree.
+    pub fn validate_restored(
+        &self,
+        skipped: &IndexSet<FactorSourceID>,
+        neglected: &IndexSet<FactorSourceID>,
+    ) -> Result<()> {
+        let own = |ids: &IndexSet<FactorSourceID>| {
+            ids.iter()
+                .filter(|id| self.references_factor_source(id))
+                .cloned()
+                .collect::<IndexSet<_>>()
+        };
+        let mismatch = |expected: IndexSet<FactorSourceID>, actual: IndexSet<FactorSourceID>| {
+            expected.symmetric_difference(&actual).next().cloned()
+        };
+        let address = &self.owned_matrix_of_factors.address_of_owner;
+        if let Some(factor_source_id) = mismatch(own(skipped), self.ids_of_skipped_factor_sources())
+        {
+            return Err(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::SkippedFactorSourceMismatch {
+                    address: address.clone(),
+                    factor_source_id,
+                },
+            ));
+        }
+        if let Some(factor_source_id) =
+            mismatch(own(neglected), self.ids_of_neglected_factor_sources())
+        {
+            return Err(CommonError::InconsistentSnapshot(
+                InconsistentSnapshotReason::NeglectedFactorSourceMismatch {
+                    address: address.clone(),
+                    factor_source_id,
+                },
+            ));
+        }
+        Ok(())
     }
 }
 impl SignaturesBuilderLevel2 {
@@ -65,7 +208,8 @@ impl SignaturesBuilderLevel2 {
 
     fn signed_override_factors(&self) -> IndexSet<SignatureByOwnedFactorForPayload> {
         self.signatures
-            .borrow()
+            .read()
+            .unwrap()
             .iter()
             .filter(|s| {
                 self.all_override_factor_source_ids()
@@ -77,7 +221,8 @@ impl SignaturesBuilderLevel2 {
 
     pub fn signed_threshold_factors(&self) -> IndexSet<SignatureByOwnedFactorForPayload> {
         self.signatures
-            .borrow()
+            .read()
+            .unwrap()
             .iter()
             .filter(|s| {
                 self.all_threshold_factor_source_ids()
@@ -119,36 +264,54 @@ impl SignaturesBuilderLevel2 {
 
     fn ids_of_factor_sources_signed_with(&self) -> IndexSet<FactorSourceID> {
         self.signatures
-            .borrow()
+            .read()
+            .unwrap()
             .clone()
             .into_iter()
-            .map(|s| s.factor_source_id().clone())
+            .map(|s| *s.factor_source_id())
             .collect::<IndexSet<_>>()
     }
 
     pub fn ids_of_skipped_factor_sources(&self) -> IndexSet<FactorSourceID> {
         self.skipped_factor_source_ids
-            .borrow()
+            .read()
+            .unwrap()
             .clone()
             .into_iter()
             .collect::<IndexSet<_>>()
     }
 
-    fn ids_of_skipped_threshold_factor_sources(&self) -> IndexSet<FactorSourceID> {
-        let threshold_factors = self.all_threshold_factor_source_ids();
+    pub fn ids_of_neglected_factor_sources(&self) -> IndexSet<FactorSourceID> {
+        self.neglected_factor_sources
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<IndexSet<_>>()
+    }
+
+    /// Neither skipped nor neglected factor sources will contribute any
+    /// signatures.
+    fn ids_of_skipped_or_neglected_factor_sources(&self) -> IndexSet<FactorSourceID> {
         self.ids_of_skipped_factor_sources()
+            .union(&self.ids_of_neglected_factor_sources())
+            .cloned()
+            .collect::<IndexSet<_>>()
+    }
+
+    fn ids_of_skipped_or_neglected_threshold_factor_sources(&self) -> IndexSet<FactorSourceID> {
+        let threshold_factors = self.all_threshold_factor_source_ids();
+        self.ids_of_skipped_or_neglected_factor_sources()
             .intersection(&threshold_factors)
-            .into_iter()
-            .map(|x| x.clone())
+            .cloned()
             .collect::<IndexSet<_>>()
     }
 
-    fn ids_of_skipped_override_factor_sources(&self) -> IndexSet<FactorSourceID> {
+    fn ids_of_skipped_or_neglected_override_factor_sources(&self) -> IndexSet<FactorSourceID> {
         let override_factors = self.all_override_factor_source_ids();
-        self.ids_of_skipped_factor_sources()
+        self.ids_of_skipped_or_neglected_factor_sources()
             .intersection(&override_factors)
-            .into_iter()
-            .map(|x| x.clone())
+            .cloned()
             .collect::<IndexSet<_>>()
     }
 
@@ -157,8 +320,7 @@ impl SignaturesBuilderLevel2 {
         let ids_of_signed = self.ids_of_factor_sources_signed_with();
         ids_of_signed
             .intersection(&override_factors)
-            .into_iter()
-            .map(|x| x.clone())
+            .cloned()
             .collect::<IndexSet<_>>()
     }
 
@@ -167,49 +329,34 @@ impl SignaturesBuilderLevel2 {
         let ids_of_signed = self.ids_of_factor_sources_signed_with();
         ids_of_signed
             .intersection(&threshold_factors)
-            .into_iter()
-            .map(|x| x.clone())
+            .cloned()
             .collect::<IndexSet<_>>()
     }
 
-    /// "done" is either "skipped" or "has signed with"
+    /// "done" is either "skipped", "neglected" or "has signed with"
     fn ids_of_done_threshold_factors(&self) -> IndexSet<FactorSourceID> {
-        let skipped = self.ids_of_skipped_threshold_factor_sources();
+        let skipped = self.ids_of_skipped_or_neglected_threshold_factor_sources();
         let signed = self.ids_of_signed_threshold_factor_sources();
-        skipped
-            .union(&signed)
-            .into_iter()
-            .map(|x| x.clone())
-            .collect::<IndexSet<_>>()
+        skipped.union(&signed).cloned().collect::<IndexSet<_>>()
     }
 
-    /// "done" is either "skipped" or "has signed with"
+    /// "done" is either "skipped", "neglected" or "has signed with"
     fn ids_of_done_override_factors(&self) -> IndexSet<FactorSourceID> {
-        let skipped = self.ids_of_skipped_override_factor_sources();
+        let skipped = self.ids_of_skipped_or_neglected_override_factor_sources();
         let signed = self.ids_of_signed_override_factor_sources();
-        skipped
-            .union(&signed)
-            .into_iter()
-            .map(|x| x.clone())
-            .collect::<IndexSet<_>>()
+        skipped.union(&signed).cloned().collect::<IndexSet<_>>()
     }
 
     fn ids_of_remaining_threshold_factors(&self) -> IndexSet<FactorSourceID> {
         let all = self.all_threshold_factor_source_ids();
         let done = self.ids_of_done_threshold_factors();
-        all.difference(&done)
-            .into_iter()
-            .map(|x| x.clone())
-            .collect::<IndexSet<_>>()
+        all.difference(&done).cloned().collect::<IndexSet<_>>()
     }
 
     fn ids_of_remaining_override_factors(&self) -> IndexSet<FactorSourceID> {
         let all = self.all_override_factor_source_ids();
         let done = self.ids_of_done_override_factors();
-        all.difference(&done)
-            .into_iter()
-            .map(|x| x.clone())
-            .collect::<IndexSet<_>>()
+        all.difference(&done).cloned().collect::<IndexSet<_>>()
     }
 
     fn all_threshold_factor_source_ids(&self) -> IndexSet<FactorSourceID> {
@@ -223,9 +370,92 @@ impl SignaturesBuilderLevel2 {
         )
     }
 
+    /// Returns `true` if the signatures requirement can no longer be
+    /// fulfilled, no matter which of the remaining factor sources are signed
+    /// with, i.e. the entity will fail auth.
+    pub fn is_irrecoverably_invalid(&self) -> bool {
+        if self.has_fulfilled_signatures_requirement() {
+            return false;
+        }
+        let can_still_sign_with_override_factor =
+            !self.ids_of_remaining_override_factors().is_empty();
+        let can_still_reach_threshold = self.threshold() > 0
+            && self.ids_of_signed_threshold_factor_sources().len()
+                + self.ids_of_remaining_threshold_factors().len()
+                >= self.threshold();
+        !can_still_sign_with_override_factor && !can_still_reach_threshold
+    }
+
+    /// Returns `true` if the signatures requirement is not fulfilled, and
+    /// could not have been even if the factor sources in `not_to_blame` had
+    /// signed, e.g. factor sources skipped automatically because the
+    /// transaction was already invalid.
+    pub fn has_failed_auth_disregarding(&self, not_to_blame: &IndexSet<FactorSourceID>) -> bool {
+        if self.has_fulfilled_signatures_requirement() {
+            return false;
+        }
+        let to_blame = self
+            .ids_of_skipped_or_neglected_factor_sources()
+            .difference(not_to_blame)
+            .cloned()
+            .collect::<IndexSet<_>>();
+        let could_have_signed_with_override_factor = self
+            .all_override_factor_source_ids()
+            .difference(&to_blame)
+            .next()
+            .is_some();
+        let could_have_reached_threshold = self.threshold() > 0
+            && self
+                .all_threshold_factor_source_ids()
+                .difference(&to_blame)
+                .count()
+                >= self.threshold();
+        !could_have_signed_with_override_factor && !could_have_reached_threshold
+    }
+
+    /// What is still needed to fulfill the signatures requirement, `None` if
+    /// it already is fulfilled.
+    pub fn remaining_requirement(&self) -> Option<RemainingSignaturesRequirement> {
+        if self.has_fulfilled_signatures_requirement() {
+            return None;
+        }
+        let (threshold_factors, threshold) = if self.threshold() == 0 {
+            // cornercase, the threshold factors can never fulfill the
+            // requirement, as in `has_fulfilled_signatures_requirement`.
+            (IndexSet::new(), 1)
+        } else {
+            (
+                self.ids_of_remaining_threshold_factors(),
+                self.threshold() - self.ids_of_signed_threshold_factor_sources().len(),
+            )
+        };
+        Some(RemainingSignaturesRequirement {
+            override_factors: self.ids_of_remaining_override_factors(),
+            threshold_factors,
+            threshold,
+        })
+    }
+
+    pub fn plan(&self) -> PlannedEntity {
+        PlannedEntity {
+            address: self.owned_matrix_of_factors.address_of_owner.clone(),
+            requirement: self.remaining_requirement(),
+        }
+    }
+
+    pub fn prompt_status(&self) -> EntityPromptStatus {
+        EntityPromptStatus {
+            address: self.owned_matrix_of_factors.address_of_owner.clone(),
+            signed: self.ids_of_factor_sources_signed_with().len(),
+            remaining: self.ids_of_remaining_override_factors().len()
+                + self.ids_of_remaining_threshold_factors().len(),
+            remaining_requirement: self.remaining_requirement(),
+        }
+    }
+
     fn can_skip_factor_source(&self, factor_source: &FactorSource) -> bool {
         let id = &factor_source.id;
-        if self.skipped_factor_source_ids.borrow().contains(id) {
+        if self.skipped_factor_source_ids.read().unwrap().contains(id) {
             // Cannot skipped twice. This is a programmer error.
             return false;
         }
@@ -235,49 +465,28 @@ impl SignaturesBuilderLevel2 {
             return true;
         }
 
-        println!("\n\n✨✨✨✨✨✨\n\n");
-
-        println!(
-            "🐙 all_threshold_factor_source_ids: {}",
-            self.all_threshold_factor_source_ids().len()
-        );
-        println!(
-            "🐙 ids_of_remaining_threshold_factors: {}",
-            self.ids_of_remaining_threshold_factors().len()
-        );
-        println!(
-            "🐙 ids_of_done_threshold_factors: {}",
-            self.ids_of_done_threshold_factors().len()
-        );
-        println!(
-            "🐙 ids_of_signed_threshold_factor_sources: {}",
-            self.ids_of_signed_threshold_factor_sources().len()
-        );
-        println!(
-            "🐙 ids_of_skipped_threshold_factor_sources: {}",
-            self.ids_of_skipped_threshold_factor_sources().len()
-        );
-        println!(
-            "🐙 ids_of_skipped_factor_sources: {}",
-            self.ids_of_skipped_factor_sources().len()
-        );
-        println!(
-            "🐙 ids_of_factor_sources_signed_with: {}",
-            self.ids_of_factor_sources_signed_with().len()
-        );
-        println!(
-            "🐙 all_override_factor_source_ids: {}",
-            self.all_override_factor_source_ids().len()
+        trace!(
+            address = ?self.owned_matrix_of_factors.address_of_owner,
+            factor_source_id = ?id,
+            threshold = self.threshold(),
+            threshold_factors = self.all_threshold_factor_source_ids().len(),
+            remaining_threshold_factors = self.ids_of_remaining_threshold_factors().len(),
+            done_threshold_factors = self.ids_of_done_threshold_factors().len(),
+            signed_threshold_factors = self.ids_of_signed_threshold_factor_sources().len(),
+            skipped_or_neglected_threshold_factors = self
+                .ids_of_skipped_or_neglected_threshold_factor_sources()
+                .len(),
+            skipped_factors = self.ids_of_skipped_factor_sources().len(),
+            signed_factors = self.ids_of_factor_sources_signed_with().len(),
+            override_factors = self.all_override_factor_source_ids().len(),
+            "Evaluating if factor source can be skipped"
         );
 
         if self.is_override_factor(id) {
             let number_of_remaining_override_factors_to_eval_including_this =
                 self.ids_of_remaining_override_factors().len() as i32;
 
-            let can_skip_factor_source =
-                number_of_remaining_override_factors_to_eval_including_this > 1;
-
-            return can_skip_factor_source;
+            number_of_remaining_override_factors_to_eval_including_this > 1
         } else if self.is_threshold_factor(id) {
             let number_of_additionally_required_threshold_factors_to_sign = self.threshold() as i32
                 - self.ids_of_signed_threshold_factor_sources().len() as i32;
@@ -287,11 +496,10 @@ impl SignaturesBuilderLevel2 {
 
             let delta = number_of_remaining_threshold_factors_to_eval_including_this
                 - number_of_additionally_required_threshold_factors_to_sign;
-            let can_skip_factor_source = delta > 0;
-
-            return can_skip_factor_source;
+            delta > 0
         } else {
-            panic!("MUST be in either overrideFactors OR in thresholdFactors (and was not in overrideFactors...)")
+            // Not a factor of this entity, skipping it cannot fail its auth.
+            true
         }
     }
 }
@@ -303,7 +511,7 @@ impl IsSignaturesBuilder for SignaturesBuilderLevel2 {
     }
 
     fn signatures(&self) -> IndexSet<SignatureByOwnedFactorForPayload> {
-        IndexSet::from_iter(self.signatures.borrow().clone())
+        IndexSet::from_iter(self.signatures.read().unwrap().clone())
     }
 
     type InvalidIfSkipped = AccountAddressOrIdentityAddress;
@@ -318,30 +526,66 @@ impl IsSignaturesBuilder for SignaturesBuilderLevel2 {
         }
     }
 
-    fn skip_factor_sources(&self, factor_source: &FactorSource) {
+    /// The `reason` is only of interest to the orchestrator, i.e. not stored.
+    fn skip_factor_sources(&self, factor_source: &FactorSource, _reason: SkipReason) {
         {
             let id = factor_source.id;
+            trace!(
+                address = ?self.owned_matrix_of_factors.address_of_owner,
+                factor_source_id = ?id,
+                "Skipping factor source"
+            );
             // assert!(self.can_skip_factor_source(factor_source)); // REINTRODUCE THIS! WE WANT THIS ASSERT!
-            assert!(!self.skipped_factor_source_ids.borrow().contains(&id));
-            self.skipped_factor_source_ids.borrow_mut().push(id);
+            assert!(!self.skipped_factor_source_ids.read().unwrap().contains(&id));
+            self.skipped_factor_source_ids.write().unwrap().push(id);
         }
 
         {
-            assert!(!self.skipped_factor_source_ids.borrow().is_empty())
+            assert!(!self.skipped_factor_source_ids.read().unwrap().is_empty())
         }
     }
 
+    fn neglect_factor_source(&self, factor_source: &FactorSource, reason: NeglectReason) {
+        let id = factor_source.id;
+        trace!(
+            address = ?self.owned_matrix_of_factors.address_of_owner,
+            factor_source_id = ?id,
+            ?reason,
+            "Neglecting factor source"
+        );
+        assert!(!self
+            .neglected_factor_sources
+            .read()
+            .unwrap()
+            .contains_key(&id));
+        self.neglected_factor_sources
+            .write()
+            .unwrap()
+            .insert(id, reason);
+    }
+
     fn append_signature(&self, signature: SignatureByOwnedFactorForPayload) {
         {
             assert_eq!(
                 signature.owned_factor_instance.owner,
                 self.owned_matrix_of_factors.address_of_owner
             );
-            assert!(!self.signatures.borrow().contains(&signature));
-            self.signatures.borrow_mut().push(signature);
+            assert!(!self.signatures.read().unwrap().contains(&signature));
+            trace!(
+                address = ?self.owned_matrix_of_factors.address_of_owner,
+                factor_source_id = ?signature.factor_source_id(),
+                "Appending signature"
+            );
+            self.signatures.write().unwrap().push(signature);
         }
         {
-            assert!(!self.signatures.borrow().is_empty())
+            assert!(!self.signatures.read().unwrap().is_empty())
+        }
+        if self.has_fulfilled_signatures_requirement() {
+            trace!(
+                address = ?self.owned_matrix_of_factors.address_of_owner,
+                "Entity fulfilled signatures requirement"
+            );
         }
     }
 }
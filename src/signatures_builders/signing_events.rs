@@ -0,0 +1,62 @@
+use crate::prelude::*;
+
+/// Progress of a signing process (context), e.g. used by a host UI to drive
+/// progress bars and per transaction status badges.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SigningEvent {
+    /// The user is being asked if she wants to sign with or skip the factor
+    /// source.
+    FactorSourcePrompted {
+        factor_source_id: FactorSourceID,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+    },
+
+    /// The user signed with the factor source, each signature carries the
+    /// intent hash and address of the entity.
+    SignedWith {
+        factor_source_id: FactorSourceID,
+        signatures: IndexSet<SignatureByOwnedFactorForPayload>,
+    },
+
+    /// The factor source was skipped - by the user or automatically, see
+    /// `reason` - making the transactions in `invalid_tx_if_skipped` invalid.
+    Skipped {
+        factor_source_id: FactorSourceID,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        reason: SkipReason,
+    },
+
+    /// Signing with the factor source failed, and the user did not retry.
+    Neglected {
+        factor_source_id: FactorSourceID,
+        reason: NeglectReason,
+    },
+
+    /// Every entity requiring auth has fulfilled its signatures requirement.
+    TransactionFulfilled {
+        intent_hash: IntentHash,
+        entities: Vec<AccountAddressOrIdentityAddress>,
+    },
+
+    /// Some entities requiring auth can no longer fulfill their signatures
+    /// requirement.
+    TransactionBecameInvalid {
+        intent_hash: IntentHash,
+        entities_which_failed_auth: Vec<AccountAddressOrIdentityAddress>,
+    },
+}
+
+/// Observer of the progress of a signing process (context), notified about
+/// every `SigningEvent` in the order they happen.
+pub trait SigningObserver: std::fmt::Debug + Send + Sync {
+    fn on_event(&self, event: SigningEvent);
+}
+
+/// Lets a host subscribe to the events as a stream, using the receiving end
+/// of the channel.
+impl SigningObserver for futures::channel::mpsc::UnboundedSender<SigningEvent> {
+    fn on_event(&self, event: SigningEvent) {
+        // The receiver being dropped means nobody is interested anymore.
+        let _ = self.unbounded_send(event);
+    }
+}
@@ -0,0 +1,238 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::prelude::*;
+
+/// Configuration of a signing process (context), the default values
+/// correspond to the behaviour of `SignaturesBuilderLevel0::new`.
+#[derive(Clone, Debug)]
+pub struct SigningOptions {
+    /// Decides the "signing order" of the factor sources.
+    pub ordering: Arc<dyn FactorSourceOrdering>,
+
+    /// Signs with the factor sources the user chose to sign with.
+    pub signer: Arc<dyn FactorSourceSigner>,
+
+    /// How to handle a factor source failing to sign.
+    pub retry_policy: RetryPolicy,
+
+    /// If factor sources of the same kind are signed with one after another
+    /// or concurrently.
+    pub concurrency: SigningConcurrency,
+
+    /// What to do with the remaining factor sources once every transaction
+    /// has fulfilled its signatures requirement.
+    pub after_all_transactions_fulfilled: AfterAllTransactionsFulfilled,
+
+    /// How long to wait for the user, or for a factor source to sign, before
+    /// neglecting the factor source, per kind.
+    pub timeouts: SigningTimeouts,
+
+    /// If set, the factor sources outside a cheapest set of factor sources
+    /// fulfilling every transaction - according to these costs - are skipped
+    /// without prompting the user, see
+    /// `SignaturesBuilderLevel0::suggested_factor_sources_to_skip`.
+    pub skip_outside_minimum_cost_set: Option<FactorSourceCosts>,
+
+    /// If signing continues after a transaction has become invalid.
+    pub batch_mode: BatchSigningMode,
+
+    /// If the user is prompted once per factor source or once per kind.
+    pub prompt_granularity: PromptGranularity,
+
+    /// Notified about the progress of the signing process, if any.
+    pub observer: Option<Arc<dyn SigningObserver>>,
+}
+
+impl Default for SigningOptions {
+    fn default() -> Self {
+        Self {
+            ordering: Arc::new(DefaultFactorSourceOrdering),
+            signer: Arc::new(DefaultFactorSourceSigner),
+            retry_policy: RetryPolicy::default(),
+            concurrency: SigningConcurrency::default(),
+            after_all_transactions_fulfilled: AfterAllTransactionsFulfilled::default(),
+            timeouts: SigningTimeouts::default(),
+            skip_outside_minimum_cost_set: None,
+            batch_mode: BatchSigningMode::default(),
+            prompt_granularity: PromptGranularity::default(),
+            observer: None,
+        }
+    }
+}
+
+/// What to do once a transaction has become invalid, i.e. can no longer be
+/// signed with enough factor sources.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash)]
+pub enum BatchSigningMode {
+    /// Signing continues, to get as many transactions as possible signed.
+    #[default]
+    BestEffort,
+
+    /// Signing is aborted with a `CommonError::TransactionInvalid` error, for
+    /// flows where the transactions are worthless unless all of them are
+    /// signed, e.g. a multi-transaction account migration.
+    AllOrNothing,
+}
+
+/// How many factor sources the user is prompted for at once, e.g. a wallet
+/// might show one screen per kind ("Sign with your Ledgers").
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash)]
+pub enum PromptGranularity {
+    /// The user is prompted for each factor source, using
+    /// `IsSigningUser::sign_or_skip`.
+    #[default]
+    PerFactorSource,
+
+    /// The user is prompted once for all factor sources of the same kind,
+    /// using `IsSigningUser::sign_or_skip_kind`. Once signing with them is
+    /// optional, see `AfterAllTransactionsFulfilled::PromptForExtraSignatures`,
+    /// she is prompted for each one using `IsSigningUser::sign_extra_or_skip`.
+    PerFactorSourceKind,
+}
+
+/// Once every transaction has fulfilled its signatures requirement, any
+/// remaining factor source is not needed, e.g. a user who signed with a Ledger
+/// used as override factor does not need to sign with any threshold factor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash)]
+pub enum AfterAllTransactionsFulfilled {
+    /// The user is prompted for every remaining factor source, just like
+    /// before all transactions were fulfilled.
+    #[default]
+    KeepPrompting,
+
+    /// Every remaining factor source is skipped, without prompting the user.
+    SkipRemaining,
+
+    /// The user is prompted for every remaining factor source using
+    /// `IsSigningUser::sign_extra_or_skip`, i.e. the signatures are optional.
+    PromptForExtraSignatures,
+}
+
+/// Factor sources of some kinds can sign in parallel without any user
+/// interaction - e.g. multiple `Device` factor sources - for which we do not
+/// need to wait for one factor source to finish signing before starting to
+/// sign with the next one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SigningConcurrency {
+    /// Factor sources are signed with one after another.
+    #[default]
+    Sequential,
+
+    /// Factor sources of the same kind - if it is one of `kinds` - are
+    /// signed with concurrently, at most `limit` at a time.
+    ConcurrentPerKind {
+        kinds: IndexSet<FactorSourceKind>,
+        limit: NonZeroUsize,
+    },
+}
+
+impl SigningConcurrency {
+    /// Panics if `limit` is `0`.
+    pub fn concurrent_per_kind(
+        kinds: impl IntoIterator<Item = FactorSourceKind>,
+        limit: usize,
+    ) -> Self {
+        Self::ConcurrentPerKind {
+            kinds: kinds.into_iter().collect(),
+            limit: NonZeroUsize::new(limit).expect("Concurrency limit must not be 0"),
+        }
+    }
+
+    /// The maximum number of factor sources of `kind` to sign with
+    /// concurrently, `None` if they should be signed with sequentially.
+    pub fn limit_for_kind(&self, kind: FactorSourceKind) -> Option<usize> {
+        match self {
+            Self::Sequential => None,
+            Self::ConcurrentPerKind { kinds, limit } => {
+                kinds.contains(&kind).then_some(limit.get())
+            }
+        }
+    }
+}
+
+/// How many times we attempt to sign with a factor source which fails, e.g.
+/// a Ledger which got disconnected. After each failure the user is asked if
+/// she wants to retry - unless the maximum number of attempts has been
+/// reached - skip or cancel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash)]
+pub struct RetryPolicy {
+    /// Number of attempts, including the first one, after which a failing
+    /// factor source is neglected.
+    max_attempts_per_factor_source: NonZeroUsize,
+}
+
+impl RetryPolicy {
+    /// Panics if `max_attempts_per_factor_source` is `0`.
+    pub fn new(max_attempts_per_factor_source: usize) -> Self {
+        Self {
+            max_attempts_per_factor_source: NonZeroUsize::new(max_attempts_per_factor_source)
+                .expect("Max attempts per factor source must not be 0"),
+        }
+    }
+
+    /// A failing factor source is neglected after the first attempt, the
+    /// user is only asked if she wants to skip it or cancel.
+    pub fn no_retries() -> Self {
+        Self::new(1)
+    }
+
+    pub fn max_attempts_per_factor_source(&self) -> usize {
+        self.max_attempts_per_factor_source.get()
+    }
+
+    pub fn can_retry_after(&self, attempts: usize) -> bool {
+        attempts < self.max_attempts_per_factor_source()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::no_retries()
+    }
+}
+
+/// Timeouts for a single factor source kind, `None` means no timeout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, std::hash::Hash)]
+pub struct FactorSourceKindTimeouts {
+    /// How long to wait for the user to decide if she wants to sign with
+    /// or skip a factor source, e.g. if she walked away from her phone.
+    pub user_decision: Option<Duration>,
+
+    /// How long to wait for a factor source to sign, e.g. a Ledger which
+    /// hangs, this is per attempt.
+    pub signing: Option<Duration>,
+}
+
+/// If a timeout expires the factor source is neglected with
+/// `NeglectReason::TimedOut` and signing continues with the next factor
+/// source. By default there are no timeouts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SigningTimeouts {
+    per_kind: IndexMap<FactorSourceKind, FactorSourceKindTimeouts>,
+}
+
+impl SigningTimeouts {
+    pub fn new(
+        per_kind: impl IntoIterator<Item = (FactorSourceKind, FactorSourceKindTimeouts)>,
+    ) -> Self {
+        Self {
+            per_kind: per_kind.into_iter().collect(),
+        }
+    }
+
+    /// Returns a copy of these timeouts using `timeouts` for `kind`.
+    pub fn with_kind(mut self, kind: FactorSourceKind, timeouts: FactorSourceKindTimeouts) -> Self {
+        self.per_kind.insert(kind, timeouts);
+        self
+    }
+
+    pub fn user_decision_for(&self, kind: FactorSourceKind) -> Option<Duration> {
+        self.per_kind.get(&kind).and_then(|t| t.user_decision)
+    }
+
+    pub fn signing_for(&self, kind: FactorSourceKind) -> Option<Duration> {
+        self.per_kind.get(&kind).and_then(|t| t.signing)
+    }
+}
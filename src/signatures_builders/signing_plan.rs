@@ -0,0 +1,120 @@
+use crate::prelude::*;
+
+/// The entities of a single transaction which a factor source signs for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServedTransaction {
+    pub intent_hash: IntentHash,
+    pub entities: Vec<AccountAddressOrIdentityAddress>,
+}
+
+/// A factor source the user will be prompted for, and what it is needed for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedFactorSource {
+    pub factor_source_id: FactorSourceID,
+
+    /// The transactions - and entities thereof - this factor source signs for.
+    pub serves: Vec<ServedTransaction>,
+
+    /// The transactions which would be invalid if this factor source was
+    /// skipped, given that no other factor source is skipped.
+    pub invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+}
+
+impl PlannedFactorSource {
+    /// Returns `true` if this factor source cannot be skipped without
+    /// invalidating a transaction.
+    pub fn is_mandatory(&self) -> bool {
+        !self.invalid_tx_if_skipped.is_empty()
+    }
+}
+
+/// An entity requiring auth, and what it needs to be signed with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedEntity {
+    pub address: AccountAddressOrIdentityAddress,
+
+    /// What is needed to fulfill the signatures requirement, `None` if it
+    /// already is fulfilled.
+    pub requirement: Option<RemainingSignaturesRequirement>,
+}
+
+/// A transaction being signed, and what each of its entities needs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedTransaction {
+    pub intent_hash: IntentHash,
+    pub entities: Vec<PlannedEntity>,
+}
+
+/// The factor sources a signing process (context) will prompt the user for,
+/// in "signing order", created without prompting the user, e.g. to tell her
+/// up front "you will need your Ledger and 2 of these 3 cards", see
+/// `SigningPlan::new` and `SignaturesBuilderLevel0::plan`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigningPlan {
+    pub factor_sources: Vec<PlannedFactorSource>,
+
+    /// Every transaction being signed, in order.
+    pub transactions: Vec<PlannedTransaction>,
+}
+
+/// The user of a signing process only created to plan it, which is never
+/// prompted, and would cancel if it was.
+struct PlanningUser;
+
+#[async_trait::async_trait]
+impl IsSigningUser for PlanningUser {
+    async fn sign_or_skip(
+        &self,
+        _factor_source: &FactorSource,
+        _invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+    ) -> SigningUserInput {
+        SigningUserInput::Cancel
+    }
+
+    async fn retry_skip_or_cancel(
+        &self,
+        _factor_source: &FactorSource,
+        _failure: CommonError,
+        _invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        _can_retry: bool,
+    ) -> SigningRetryInput {
+        SigningRetryInput::Cancel
+    }
+}
+
+impl SigningPlan {
+    /// Plans signing `transactions`, without a user, e.g. before the user
+    /// starts signing. Fails just like
+    /// `SignaturesBuilderLevel0::new_with_options`.
+    pub fn new(
+        all_factor_sources_in_profile: IndexSet<FactorSource>,
+        transactions: IndexSet<TransactionIntent>,
+        options: SigningOptions,
+    ) -> Result<Self> {
+        SignaturesBuilderLevel0::new_with_options(
+            SigningUser::host(PlanningUser),
+            all_factor_sources_in_profile,
+            transactions,
+            options,
+        )
+        .map(|context| context.plan())
+    }
+
+    /// The factor sources which cannot be skipped without invalidating a
+    /// transaction, in "signing order".
+    pub fn mandatory(&self) -> Vec<&PlannedFactorSource> {
+        self.factor_sources
+            .iter()
+            .filter(|f| f.is_mandatory())
+            .collect()
+    }
+
+    /// The factor sources which - one at a time - can be skipped without
+    /// invalidating any transaction, in "signing order".
+    pub fn optional(&self) -> Vec<&PlannedFactorSource> {
+        self.factor_sources
+            .iter()
+            .filter(|f| !f.is_mandatory())
+            .collect()
+    }
+}
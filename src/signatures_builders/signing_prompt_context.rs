@@ -0,0 +1,57 @@
+use crate::prelude::*;
+
+/// Signing progress of an entity requiring auth, when the user is prompted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityPromptStatus {
+    pub address: AccountAddressOrIdentityAddress,
+
+    /// Number of factor sources of the entity which have signed.
+    pub signed: usize,
+
+    /// Number of factor sources of the entity which can still sign, i.e.
+    /// neither signed with, skipped nor neglected.
+    pub remaining: usize,
+
+    /// What is still needed to fulfill the signatures requirement, `None` if
+    /// it already is fulfilled.
+    pub remaining_requirement: Option<RemainingSignaturesRequirement>,
+}
+
+/// Signing progress of a transaction, when the user is prompted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionPromptStatus {
+    pub intent_hash: IntentHash,
+    pub status: TransactionSigningStatus,
+    pub entities: Vec<EntityPromptStatus>,
+}
+
+/// Everything a user is told when prompted to sign with or skip a factor
+/// source, e.g. for a host UI to say "2 of 3 transactions are already
+/// fully signed" or "this is the last Ledger that can save Carla's
+/// transfer".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigningPromptContext {
+    /// The transactions which would be invalid if the factor source was
+    /// skipped.
+    pub invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+
+    /// Progress of every transaction being signed, in order.
+    pub transactions: Vec<TransactionPromptStatus>,
+
+    /// Zero-based position of the factor source in "signing order".
+    pub position: usize,
+
+    /// Number of factor sources in "signing order", including ones already
+    /// evaluated.
+    pub number_of_factor_sources: usize,
+}
+
+impl SigningPromptContext {
+    /// Number of transactions with `status`.
+    pub fn number_of_transactions_with_status(&self, status: TransactionSigningStatus) -> usize {
+        self.transactions
+            .iter()
+            .filter(|t| t.status == status)
+            .count()
+    }
+}
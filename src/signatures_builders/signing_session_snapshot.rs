@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// A factor source which was neglected, and why.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NeglectedFactorSource {
+    pub factor_source_id: FactorSourceID,
+    pub reason: NeglectReason,
+}
+
+/// A factor source which was skipped, and why.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFactorSource {
+    pub factor_source_id: FactorSourceID,
+    pub reason: SkipReason,
+}
+
+/// The state of the `SignaturesBuilderLevel2` of a single entity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntitySigningSnapshot {
+    pub address: AccountAddressOrIdentityAddress,
+    pub skipped_factor_source_ids: Vec<FactorSourceID>,
+    pub neglected_factor_sources: Vec<NeglectedFactorSource>,
+    pub signatures: Vec<SignatureByOwnedFactorForPayload>,
+}
+
+/// The state of the `SignaturesBuilderLevel1` of a single transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionSigningSnapshot {
+    pub intent_hash: IntentHash,
+    pub entities: Vec<EntitySigningSnapshot>,
+}
+
+/// A serializable snapshot of the state of a signing process (context), e.g.
+/// to persist if the app is suspended mid-signing, and later continue - with
+/// the next factor source not yet evaluated - using
+/// `SignaturesBuilderLevel0::restore`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningSessionSnapshot {
+    pub transactions: Vec<TransactionSigningSnapshot>,
+
+    /// The factor sources which were skipped, and why, in order.
+    pub skipped_factor_sources: Vec<SkippedFactorSource>,
+
+    /// The factor sources which failed to sign, and why, in order.
+    pub neglected_factor_sources: Vec<NeglectedFactorSource>,
+}
+
+/// Why a `SigningSessionSnapshot` cannot be restored, see
+/// `SignaturesBuilderLevel0::restore`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InconsistentSnapshotReason {
+    /// The factor source was skipped or neglected, but is not used by any of
+    /// the transactions.
+    FactorSourceNotSignedWith(FactorSourceID),
+
+    /// The factor source was skipped, neglected or signed with for the
+    /// entity, but is not one of its factors.
+    FactorSourceNotOfEntity {
+        address: AccountAddressOrIdentityAddress,
+        factor_source_id: FactorSourceID,
+    },
+
+    /// The factor source was skipped, neglected or signed with more than
+    /// once for the entity, e.g. both skipped and signed with.
+    FactorSourceEvaluatedMoreThanOnce {
+        address: AccountAddressOrIdentityAddress,
+        factor_source_id: FactorSourceID,
+    },
+
+    /// A signature of the entity is for another transaction than the one
+    /// with `intent_hash` the entity requires auth for.
+    SignatureOfOtherTransaction {
+        intent_hash: IntentHash,
+        address: AccountAddressOrIdentityAddress,
+        factor_source_id: FactorSourceID,
+    },
+
+    /// A signature of the entity is not by the factor instance of the
+    /// factor source in its matrix, or is owned by another entity.
+    SignatureOfOtherFactorInstance {
+        address: AccountAddressOrIdentityAddress,
+        factor_source_id: FactorSourceID,
+    },
+
+    /// The factor source was skipped for the entity but not for the whole
+    /// signing process, or vice versa.
+    SkippedFactorSourceMismatch {
+        address: AccountAddressOrIdentityAddress,
+        factor_source_id: FactorSourceID,
+    },
+
+    /// The factor source was neglected for the entity but not for the whole
+    /// signing process, or vice versa.
+    NeglectedFactorSourceMismatch {
+        address: AccountAddressOrIdentityAddress,
+        factor_source_id: FactorSourceID,
+    },
+}
@@ -1,5 +1,7 @@
+mod recording_user;
 mod sargon;
 mod user;
 
+pub use recording_user::*;
 pub use sargon::*;
 pub use user::*;
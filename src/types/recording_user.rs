@@ -0,0 +1,278 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A prompt of a signing user and her answer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscribedPrompt {
+    SignOrSkip {
+        factor_source_id: FactorSourceID,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        answer: SigningUserInput,
+    },
+    SignOrSkipKind {
+        kind: FactorSourceKind,
+        invalid_tx_if_skipped_per_factor_source:
+            Vec<(FactorSourceID, IndexSet<InvalidTransactionIfSkipped>)>,
+        answer: SigningKindUserInput,
+    },
+    SignExtraOrSkip {
+        factor_source_id: FactorSourceID,
+        answer: SigningUserInput,
+    },
+    RetrySkipOrCancel {
+        factor_source_id: FactorSourceID,
+        failure: CommonError,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        can_retry: bool,
+        answer: SigningRetryInput,
+    },
+}
+
+/// A prompt of a signing user and her answer, and when she answered.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub answered_at: SystemTime,
+    pub prompt: TranscribedPrompt,
+}
+
+/// Every prompt of a signing user and her answer, in order, e.g. attached to
+/// a bug report and turned into a regression test using `Replay`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningTranscript {
+    pub entries: Vec<TranscriptEntry>,
+}
+
+/// Handle to the `SigningTranscript` of a `RecordingSigningUser`, readable
+/// after the user has been moved into a signing process.
+#[derive(Clone, Debug, Default)]
+pub struct SharedSigningTranscript(Arc<Mutex<SigningTranscript>>);
+impl SharedSigningTranscript {
+    /// The transcript recorded so far.
+    pub fn snapshot(&self) -> SigningTranscript {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn record(&self, prompt: TranscribedPrompt) {
+        self.0.lock().unwrap().entries.push(TranscriptEntry {
+            answered_at: SystemTime::now(),
+            prompt,
+        });
+    }
+}
+
+/// Decorates any `IsSigningUser`, recording every prompt and the answer of
+/// the decorated user into a `SigningTranscript`.
+pub struct RecordingSigningUser<U> {
+    inner: U,
+    transcript: SharedSigningTranscript,
+}
+
+impl<U> RecordingSigningUser<U> {
+    pub fn new(inner: U) -> Self {
+        Self {
+            inner,
+            transcript: SharedSigningTranscript::default(),
+        }
+    }
+
+    pub fn transcript(&self) -> SharedSigningTranscript {
+        self.transcript.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl<U: IsSigningUser + Send + Sync> IsSigningUser for RecordingSigningUser<U> {
+    async fn sign_or_skip(
+        &self,
+        factor_source: &FactorSource,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+    ) -> SigningUserInput {
+        let answer = self
+            .inner
+            .sign_or_skip(factor_source, invalid_tx_if_skipped.clone())
+            .await;
+        self.transcript.record(TranscribedPrompt::SignOrSkip {
+            factor_source_id: factor_source.id,
+            invalid_tx_if_skipped,
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    async fn sign_or_skip_in_context(
+        &self,
+        factor_source: &FactorSource,
+        context: SigningPromptContext,
+    ) -> SigningUserInput {
+        let invalid_tx_if_skipped = context.invalid_tx_if_skipped.clone();
+        let answer = self
+            .inner
+            .sign_or_skip_in_context(factor_source, context)
+            .await;
+        self.transcript.record(TranscribedPrompt::SignOrSkip {
+            factor_source_id: factor_source.id,
+            invalid_tx_if_skipped,
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    async fn sign_or_skip_kind(
+        &self,
+        kind: FactorSourceKind,
+        invalid_tx_if_skipped_per_factor_source: IndexMap<
+            FactorSource,
+            IndexSet<InvalidTransactionIfSkipped>,
+        >,
+    ) -> SigningKindUserInput {
+        let transcribed = invalid_tx_if_skipped_per_factor_source
+            .iter()
+            .map(|(factor_source, invalid)| (factor_source.id, invalid.clone()))
+            .collect();
+        let answer = self
+            .inner
+            .sign_or_skip_kind(kind, invalid_tx_if_skipped_per_factor_source)
+            .await;
+        self.transcript.record(TranscribedPrompt::SignOrSkipKind {
+            kind,
+            invalid_tx_if_skipped_per_factor_source: transcribed,
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    async fn sign_extra_or_skip(&self, factor_source: &FactorSource) -> SigningUserInput {
+        let answer = self.inner.sign_extra_or_skip(factor_source).await;
+        self.transcript.record(TranscribedPrompt::SignExtraOrSkip {
+            factor_source_id: factor_source.id,
+            answer: answer.clone(),
+        });
+        answer
+    }
+
+    async fn retry_skip_or_cancel(
+        &self,
+        factor_source: &FactorSource,
+        failure: CommonError,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        can_retry: bool,
+    ) -> SigningRetryInput {
+        let answer = self
+            .inner
+            .retry_skip_or_cancel(
+                factor_source,
+                failure.clone(),
+                invalid_tx_if_skipped.clone(),
+                can_retry,
+            )
+            .await;
+        self.transcript
+            .record(TranscribedPrompt::RetrySkipOrCancel {
+                factor_source_id: factor_source.id,
+                failure,
+                invalid_tx_if_skipped,
+                can_retry,
+                answer: answer.clone(),
+            });
+        answer
+    }
+}
+
+/// Answers of a `TestSigningUser::Replaying`, in the order of a recorded
+/// `SigningTranscript`. Only the kind of prompt and the factor source are
+/// matched, so the transactions may differ from the recorded ones.
+///
+/// Panics if prompted differently than recorded, or - when dropped - if
+/// some entries were never replayed, failing the test.
+pub struct Replay {
+    entries: Mutex<VecDeque<TranscribedPrompt>>,
+}
+
+impl Replay {
+    pub fn new(transcript: SigningTranscript) -> Self {
+        Self {
+            entries: Mutex::new(transcript.entries.into_iter().map(|e| e.prompt).collect()),
+        }
+    }
+
+    fn next(&self, prompted: impl std::fmt::Debug) -> TranscribedPrompt {
+        self.entries
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| panic!("Unexpected prompt {:?}, transcript is exhausted", prompted))
+    }
+
+    pub(crate) fn sign_or_skip(&self, factor_source: &FactorSource) -> SigningUserInput {
+        match self.next(factor_source.id) {
+            TranscribedPrompt::SignOrSkip {
+                factor_source_id,
+                answer,
+                ..
+            } if factor_source_id == factor_source.id => answer,
+            recorded => panic!(
+                "Unexpected prompt for {:?}, transcript recorded {:?}",
+                factor_source.id, recorded
+            ),
+        }
+    }
+
+    pub(crate) fn sign_or_skip_kind(&self, kind: FactorSourceKind) -> SigningKindUserInput {
+        match self.next(kind) {
+            TranscribedPrompt::SignOrSkipKind {
+                kind: recorded_kind,
+                answer,
+                ..
+            } if recorded_kind == kind => answer,
+            recorded => panic!(
+                "Unexpected prompt for {:?}, transcript recorded {:?}",
+                kind, recorded
+            ),
+        }
+    }
+
+    pub(crate) fn sign_extra_or_skip(&self, factor_source: &FactorSource) -> SigningUserInput {
+        match self.next(factor_source.id) {
+            TranscribedPrompt::SignExtraOrSkip {
+                factor_source_id,
+                answer,
+            } if factor_source_id == factor_source.id => answer,
+            recorded => panic!(
+                "Unexpected prompt for extra signature by {:?}, transcript recorded {:?}",
+                factor_source.id, recorded
+            ),
+        }
+    }
+
+    pub(crate) fn retry_skip_or_cancel(&self, factor_source: &FactorSource) -> SigningRetryInput {
+        match self.next(factor_source.id) {
+            TranscribedPrompt::RetrySkipOrCancel {
+                factor_source_id,
+                answer,
+                ..
+            } if factor_source_id == factor_source.id => answer,
+            recorded => panic!(
+                "Unexpected retry prompt for {:?}, transcript recorded {:?}",
+                factor_source.id, recorded
+            ),
+        }
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        let entries = self.entries.lock().unwrap();
+        assert!(
+            entries.is_empty(),
+            "Transcript entries never replayed: {:?}",
+            entries
+        );
+    }
+}
@@ -2,9 +2,10 @@ use std::time::SystemTime;
 
 use crate::prelude::*;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct FactorSourceID {
     pub kind: FactorSourceKind,
     pub id: Uuid,
@@ -92,7 +93,9 @@ impl FactorSource {
 }
 
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub enum FactorSourceKind {
     Ledger,
     Arculus,
@@ -102,7 +105,7 @@ pub enum FactorSourceKind {
     Device,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct FactorInstance {
     pub index: u32,
     pub factor_source_id: FactorSourceID,
@@ -116,7 +119,7 @@ impl FactorInstance {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct OwnedFactorInstance {
     pub factor_instance: FactorInstance,
     pub owner: AccountAddressOrIdentityAddress,
@@ -130,7 +133,7 @@ impl OwnedFactorInstance {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct Hash {
     id: Uuid,
 }
@@ -139,6 +142,11 @@ impl Hash {
         Self { id: Uuid::new_v4() }
     }
 }
+impl std::fmt::Display for Hash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
 pub enum EntitySecurityState {
@@ -171,7 +179,7 @@ pub struct AccountAddress;
 #[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash)]
 pub struct IdentityAddress;
 
-#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct AccountAddressOrIdentityAddress {
     pub name: String,
     id: Uuid,
@@ -191,7 +199,8 @@ pub struct Entity {
     pub security_state: EntitySecurityState,
 }
 impl Entity {
-    fn new(name: impl AsRef<str>, security_state: impl Into<EntitySecurityState>) -> Self {
+    /// An entity named `name` with a new random address.
+    pub fn new(name: impl AsRef<str>, security_state: impl Into<EntitySecurityState>) -> Self {
         Self {
             address: AccountAddressOrIdentityAddress::new(name),
             security_state: security_state.into(),
@@ -265,6 +274,53 @@ impl MatrixOfFactorInstances {
     pub fn single_threshold(factor: FactorInstance) -> Self {
         Self::threshold_only([factor], 1)
     }
+
+    /// Returns `Err` if no combination of signatures could ever fulfill this
+    /// matrix, or if it is ambiguous which role a factor source has, e.g. a
+    /// matrix of a malformed Profile.
+    pub fn validate(&self) -> Result<(), InvalidMatrixReason> {
+        if self.threshold_factors.is_empty() && self.override_factors.is_empty() {
+            return Err(InvalidMatrixReason::NoFactors);
+        }
+        if self.threshold as usize > self.threshold_factors.len() {
+            return Err(InvalidMatrixReason::ThresholdExceedsThresholdFactors {
+                threshold: self.threshold,
+                number_of_threshold_factors: self.threshold_factors.len(),
+            });
+        }
+        if self.threshold == 0 && !self.threshold_factors.is_empty() {
+            return Err(InvalidMatrixReason::ZeroThreshold);
+        }
+        let mut ids = HashSet::<FactorSourceID>::new();
+        for factor in self.threshold_factors.iter().chain(&self.override_factors) {
+            if !ids.insert(factor.factor_source_id) {
+                return Err(InvalidMatrixReason::DuplicateFactorSource(
+                    factor.factor_source_id,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a `MatrixOfFactorInstances` is invalid, see `validate`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvalidMatrixReason {
+    /// The matrix has neither threshold nor override factors.
+    NoFactors,
+
+    /// More signatures are required than there are threshold factors.
+    ThresholdExceedsThresholdFactors {
+        threshold: u8,
+        number_of_threshold_factors: usize,
+    },
+
+    /// The matrix has threshold factors but a threshold of zero, so they
+    /// could never fulfill it.
+    ZeroThreshold,
+
+    /// The factor source is used more than once, in either role.
+    DuplicateFactorSource(FactorSourceID),
 }
 
 /// For unsecurified entities we map single factor -> single threshold factor.
@@ -296,10 +352,11 @@ impl OwnedMatrixOfFactorInstances {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct IntentHash {
     hash: Hash,
 }
+#[allow(clippy::new_without_default)]
 impl IntentHash {
     pub fn generate() -> Self {
         Self {
@@ -313,6 +370,11 @@ impl IntentHash {
         self.hash.clone()
     }
 }
+impl std::fmt::Display for IntentHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.hash.fmt(f)
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
 pub struct TransactionIntent {
@@ -333,10 +395,10 @@ pub struct TransactionsPayloads {
     pub intents: IndexMap<IntentHash, TransactionIntent>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct Signature;
 
-#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct SignatureByOwnedFactorForPayload {
     pub intent_hash: IntentHash,
     pub owned_factor_instance: OwnedFactorInstance,
@@ -361,25 +423,207 @@ impl SignatureByOwnedFactorForPayload {
 
 pub type Result<T, E = CommonError> = std::result::Result<T, E>;
 
-#[derive(thiserror::Error, Clone, Debug)]
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CommonError {
-    #[error("Unknown factor source")]
-    UnknownFactorSource,
+    #[error("Unknown factor source {0:?}, not in Profile")]
+    UnknownFactorSource(FactorSourceID),
+
+    #[error("Invalid matrix of entity {address:?}, reason: {reason:?}")]
+    InvalidMatrix {
+        address: AccountAddressOrIdentityAddress,
+        reason: InvalidMatrixReason,
+    },
+
+    #[error("Entity {address:?} requires auth more than once in transaction {intent_hash:?}")]
+    DuplicateEntity {
+        intent_hash: IntentHash,
+        address: AccountAddressOrIdentityAddress,
+    },
+
+    #[error("Unknown transaction {0:?}, not being signed")]
+    UnknownIntent(IntentHash),
+
+    #[error("Unknown entity {address:?}, not requiring auth in transaction {intent_hash:?}")]
+    UnknownEntity {
+        intent_hash: IntentHash,
+        address: AccountAddressOrIdentityAddress,
+    },
+
+    #[error("Inconsistent signing session snapshot, reason: {0:?}")]
+    InconsistentSnapshot(InconsistentSnapshotReason),
+
+    #[error("Factor source {factor_source_id:?} did not sign exactly the owned factor instances of transaction {intent_hash:?}")]
+    UnexpectedSignatures {
+        factor_source_id: FactorSourceID,
+        intent_hash: IntentHash,
+    },
+
+    #[error("Factor source disconnected during signing")]
+    FactorSourceDisconnected,
+
+    #[error("Factor source removed during signing")]
+    FactorSourceRemoved,
+
+    #[error("Wrong PIN")]
+    WrongPin,
+
+    #[error("Transaction {intent_hash:?} is invalid, entities failed auth: {entities_which_failed_auth:?}")]
+    TransactionInvalid {
+        intent_hash: IntentHash,
+        entities_which_failed_auth: Vec<AccountAddressOrIdentityAddress>,
+    },
+}
+
+/// The reason why a factor source was neglected, that is, not signed with
+/// even though the user did not skip it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NeglectReason {
+    /// Signing with the factor source failed.
+    Failure(CommonError),
+
+    /// The user did not decide if she wants to sign with the factor source,
+    /// or the factor source did not sign, in time, see `SigningTimeouts`.
+    TimedOut,
+}
+
+/// The reason why a factor source was skipped, that is, not signed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// The user chose to skip the factor source.
+    User,
+
+    /// Every transaction had already fulfilled its signatures requirement,
+    /// see `AfterAllTransactionsFulfilled::SkipRemaining`.
+    AllTransactionsFulfilled,
+
+    /// Every transaction the factor source would sign was already
+    /// irrecoverably invalid, so signing with it would be pointless.
+    OnlyServesInvalidTransactions,
+
+    /// The factor source was outside the minimum cost set of factor sources,
+    /// see `SigningOptions::skip_outside_minimum_cost_set`.
+    OutsideMinimumCostSet,
 }
 
+/// The signing status of a transaction, during the signing process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, std::hash::Hash)]
+pub enum TransactionSigningStatus {
+    /// Every entity requiring auth has fulfilled its signatures requirement.
+    Fulfilled,
+
+    /// Some entity requiring auth has not yet fulfilled its signatures
+    /// requirement, but still can.
+    Pending,
+
+    /// Some entity requiring auth can no longer fulfill its signatures
+    /// requirement, the transaction will fail.
+    Invalid,
+}
+
+/// A transaction for which every entity requiring auth has fulfilled its
+/// signatures requirement, i.e. it is submittable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedTransaction {
+    pub intent_hash: IntentHash,
+    pub signatures: IndexSet<SignatureByOwnedFactorForPayload>,
+}
+impl SignedTransaction {
+    pub fn new(
+        intent_hash: IntentHash,
+        signatures: IndexSet<SignatureByOwnedFactorForPayload>,
+    ) -> Self {
+        Self {
+            intent_hash,
+            signatures,
+        }
+    }
+}
+
+/// A transaction for which at least one entity requiring auth has not
+/// fulfilled its signatures requirement, i.e. it is **not** submittable.
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+pub struct FailedTransaction {
+    pub intent_hash: IntentHash,
+    pub entities_which_failed_auth: Vec<AccountAddressOrIdentityAddress>,
+}
+impl FailedTransaction {
+    pub fn new(
+        intent_hash: IntentHash,
+        entities_which_failed_auth: Vec<AccountAddressOrIdentityAddress>,
+    ) -> Self {
+        Self {
+            intent_hash,
+            entities_which_failed_auth,
+        }
+    }
+}
+
+/// The outcome of having gone through all factor sources, partitions the
+/// transactions into successful (submittable) and failed ones.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Signatures {
-    /// **ALL** signatures:
+pub struct SignaturesOutcome {
+    /// Transactions which can be submitted, with their signatures.
+    pub successful_transactions: Vec<SignedTransaction>,
+
+    /// Transactions which cannot be submitted, with the entities which
+    /// failed auth.
+    pub failed_transactions: Vec<FailedTransaction>,
+
+    /// IDs of the factor sources which were skipped, and why, in the order
+    /// they were skipped.
+    pub skipped_factor_sources: IndexMap<FactorSourceID, SkipReason>,
+
+    /// IDs of the factor sources which failed to sign, and why, in the order
+    /// they failed.
+    pub neglected_factor_sources: IndexMap<FactorSourceID, NeglectReason>,
+}
+impl SignaturesOutcome {
+    /// Returns `true` if every transaction is submittable.
+    pub fn successful(&self) -> bool {
+        self.failed_transactions.is_empty()
+    }
+
+    /// All signatures of all successful transactions:
     /// ```ignore
-    /// for     each    transaction
+    /// for     each    successful transaction
     /// by      every   entities
     /// with    some    factor sources
     /// of      all     factor instances
     /// ```
-    pub all_signatures: IndexSet<SignatureByOwnedFactorForPayload>,
+    pub fn all_signatures(&self) -> IndexSet<SignatureByOwnedFactorForPayload> {
+        self.successful_transactions
+            .iter()
+            .flat_map(|t| t.signatures.clone())
+            .collect()
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash)]
+/// The outcome of a signing process (context), either the user went through
+/// all factor sources, or she cancelled the process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SigningOutcome {
+    /// All factor sources have been either signed with or skipped.
+    Signed(SignaturesOutcome),
+
+    /// The user cancelled the whole signing process, no signatures are
+    /// returned, not even the ones collected before cancellation.
+    Cancelled,
+}
+impl SigningOutcome {
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
+    /// Returns `None` if the signing process was cancelled.
+    pub fn signatures(self) -> Option<SignaturesOutcome> {
+        match self {
+            Self::Signed(outcome) => Some(outcome),
+            Self::Cancelled => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, std::hash::Hash, Serialize, Deserialize)]
 pub struct InvalidTransactionIfSkipped {
     pub intent_hash: IntentHash,
     pub entities_which_would_fail_auth: Vec<AccountAddressOrIdentityAddress>,
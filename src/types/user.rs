@@ -1,10 +1,49 @@
 use crate::prelude::*;
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SigningUserInput {
+    /// The user wants to sign with the factor source.
     Sign,
+
+    /// The user wants to skip signing with the factor source.
+    Skip,
+
+    /// The user wants to skip signing with the factor source and every
+    /// remaining factor source of the same kind, e.g. "I don't have any
+    /// Ledger with me".
+    SkipAllOfKind,
+
+    /// The user wants to cancel the whole signing process (context), no
+    /// subsequent factor sources will be prompted for.
+    Cancel,
+}
+
+/// The answer of a user prompted for all factor sources of a kind at once.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SigningKindUserInput {
+    /// The user wants to sign with the factor sources with these IDs, and
+    /// skip all other factor sources of the kind.
+    SignWith(IndexSet<FactorSourceID>),
+
+    /// The user wants to cancel the whole signing process (context).
+    Cancel,
+}
+
+/// The answer of a user after signing with a factor source failed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SigningRetryInput {
+    /// The user wants to try to sign with the factor source again, e.g.
+    /// after having re-plugged her Ledger.
+    Retry,
+
+    /// The user gives up on the factor source, which will be neglected.
     Skip,
+
+    /// The user wants to cancel the whole signing process (context).
+    Cancel,
 }
 
 #[async_trait::async_trait]
@@ -14,11 +53,92 @@ pub trait IsSigningUser {
         factor_source: &FactorSource,
         invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
     ) -> SigningUserInput;
+
+    /// Called instead of `sign_or_skip` - if the signing process is
+    /// configured with `PromptGranularity::PerFactorSource` - with the
+    /// `context` of the prompt, e.g. the progress of every transaction.
+    ///
+    /// Defaults to `sign_or_skip` with the `invalid_tx_if_skipped` of
+    /// `context`.
+    async fn sign_or_skip_in_context(
+        &self,
+        factor_source: &FactorSource,
+        context: SigningPromptContext,
+    ) -> SigningUserInput {
+        self.sign_or_skip(factor_source, context.invalid_tx_if_skipped)
+            .await
+    }
+
+    /// Called instead of `sign_or_skip` - if the signing process is
+    /// configured with `PromptGranularity::PerFactorSourceKind` - with every
+    /// factor source of `kind` and the transactions which would be invalid if
+    /// that factor source alone was skipped.
+    ///
+    /// Defaults to prompting for each factor source using `sign_or_skip`.
+    async fn sign_or_skip_kind(
+        &self,
+        _kind: FactorSourceKind,
+        invalid_tx_if_skipped_per_factor_source: IndexMap<
+            FactorSource,
+            IndexSet<InvalidTransactionIfSkipped>,
+        >,
+    ) -> SigningKindUserInput {
+        sign_or_skip_each(self, invalid_tx_if_skipped_per_factor_source).await
+    }
+
+    /// Called instead of `sign_or_skip` once all transactions have fulfilled
+    /// their signatures requirement - if the signing process is configured
+    /// with `AfterAllTransactionsFulfilled::PromptForExtraSignatures` - i.e.
+    /// signing with `factor_source` is optional.
+    async fn sign_extra_or_skip(&self, factor_source: &FactorSource) -> SigningUserInput {
+        self.sign_or_skip(factor_source, IndexSet::new()).await
+    }
+
+    /// Called when signing with `factor_source` failed with `failure`.
+    /// `can_retry` is `false` if the `RetryPolicy` allows for no further
+    /// attempt, in which case only skip or cancel should be offered, a
+    /// `SigningRetryInput::Retry` is treated as `SigningRetryInput::Skip`.
+    async fn retry_skip_or_cancel(
+        &self,
+        factor_source: &FactorSource,
+        failure: CommonError,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        can_retry: bool,
+    ) -> SigningRetryInput;
+}
+
+/// Prompts `user` for each factor source using `sign_or_skip`, the default
+/// of `IsSigningUser::sign_or_skip_kind`.
+pub async fn sign_or_skip_each(
+    user: &(impl IsSigningUser + Sync + ?Sized),
+    invalid_tx_if_skipped_per_factor_source: IndexMap<
+        FactorSource,
+        IndexSet<InvalidTransactionIfSkipped>,
+    >,
+) -> SigningKindUserInput {
+    let mut sign_with = IndexSet::new();
+    for (factor_source, invalid_tx_if_skipped) in invalid_tx_if_skipped_per_factor_source {
+        match user
+            .sign_or_skip(&factor_source, invalid_tx_if_skipped)
+            .await
+        {
+            SigningUserInput::Sign => {
+                sign_with.insert(factor_source.id);
+            }
+            SigningUserInput::Skip => {}
+            SigningUserInput::SkipAllOfKind => break,
+            SigningUserInput::Cancel => return SigningKindUserInput::Cancel,
+        }
+    }
+    SigningKindUserInput::SignWith(sign_with)
 }
 
+// A user exists once per signing process, so the size of the RNG of
+// `Randomness` inline does not matter.
+#[allow(clippy::large_enum_variant)]
 pub enum TestSigningUser {
     /// Emulation of a "prudent" user, that signs with all factors sources, i.e.
-    /// she never ever "skips" a factor source
+    /// she never ever "skips" a factor source, and always retries failing ones.
     Prudent,
 
     /// Emulation of a "lazy" user, that skips signing with as many factor
@@ -26,10 +146,33 @@ pub enum TestSigningUser {
     Lazy(Laziness),
 
     /// Emulation of a "random" user, that skips signing some factor sources
-    ///  at random.
-    Random,
+    /// at random, reproducibly given the seed of the `Randomness`.
+    Random(Randomness),
+
+    /// Emulation of a user who walked away, never answering any prompt.
+    Unresponsive,
+
+    /// Emulation of a user answering prompts exactly as scripted, in order,
+    /// panicking on any prompt not matching the next scripted entry.
+    Scripted(Script),
+
+    /// Emulation of a user answering prompts exactly as recorded in a
+    /// `SigningTranscript`, see `Replay`.
+    Replaying(Replay),
 }
 impl TestSigningUser {
+    /// Skips every factor source with probability 0.5.
+    pub fn random(seed: u64) -> Self {
+        Self::Random(Randomness::new(seed))
+    }
+    pub fn replaying(transcript: SigningTranscript) -> Self {
+        Self::Replaying(Replay::new(transcript))
+    }
+    pub fn scripted(
+        entries: impl IntoIterator<Item = (impl Into<ScriptedPrompt>, impl Into<ScriptedAnswer>)>,
+    ) -> Self {
+        Self::Scripted(Script::new(entries))
+    }
     pub fn lazy_always_skip() -> Self {
         Self::Lazy(Laziness::always_skip())
     }
@@ -39,8 +182,13 @@ impl TestSigningUser {
     }
 }
 
+type LazinessAct =
+    dyn Fn(&FactorSource, IndexSet<InvalidTransactionIfSkipped>) -> SigningUserInput + Send + Sync;
+type LazinessOnFailure = dyn Fn(&FactorSource, CommonError) -> SigningRetryInput + Send + Sync;
+
 pub struct Laziness {
-    act: Box<dyn Fn(&FactorSource, IndexSet<InvalidTransactionIfSkipped>) -> SigningUserInput>,
+    act: Box<LazinessAct>,
+    on_failure: Box<LazinessOnFailure>,
 }
 // impl std::fmt::Debug for Laziness {
 //     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -50,9 +198,26 @@ pub struct Laziness {
 // }
 impl Laziness {
     pub fn new(
-        act: impl Fn(&FactorSource, IndexSet<InvalidTransactionIfSkipped>) -> SigningUserInput + 'static,
+        act: impl Fn(&FactorSource, IndexSet<InvalidTransactionIfSkipped>) -> SigningUserInput
+            + Send
+            + Sync
+            + 'static,
     ) -> Self {
-        Self { act: Box::new(act) }
+        Self {
+            act: Box::new(act),
+            on_failure: Box::new(|_, _| SigningRetryInput::Skip),
+        }
+    }
+    /// By default a lazy user gives up on a failing factor source, this
+    /// lets her act otherwise.
+    pub fn on_failure(
+        self,
+        on_failure: impl Fn(&FactorSource, CommonError) -> SigningRetryInput + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            on_failure: Box::new(on_failure),
+            ..self
+        }
     }
     pub fn always_skip() -> Self {
         Self::new(|_, _| SigningUserInput::Skip)
@@ -69,6 +234,211 @@ impl Laziness {
     }
 }
 
+/// Seeded source of the answers of a `TestSigningUser::Random`, so that a
+/// failure found by it can be reproduced by using the same seed, using an
+/// RNG whose output is portable across platforms and `rand` versions.
+pub struct Randomness {
+    rng: std::sync::Mutex<ChaCha8Rng>,
+    skip_probability: f64,
+    skip_probability_per_kind: HashMap<FactorSourceKind, f64>,
+}
+impl Randomness {
+    /// Skips every factor source with probability 0.5.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: std::sync::Mutex::new(ChaCha8Rng::seed_from_u64(seed)),
+            skip_probability: 0.5,
+            skip_probability_per_kind: HashMap::new(),
+        }
+    }
+    /// Skips factor sources of kinds without a probability of their own with
+    /// `skip_probability`, panics if it is not in `0.0..=1.0`.
+    pub fn with_skip_probability(self, skip_probability: f64) -> Self {
+        Self::assert_is_probability(skip_probability);
+        Self {
+            skip_probability,
+            ..self
+        }
+    }
+    /// Skips factor sources of `kind` with `skip_probability`, panics if it
+    /// is not in `0.0..=1.0`.
+    pub fn with_skip_probability_for_kind(
+        mut self,
+        kind: FactorSourceKind,
+        skip_probability: f64,
+    ) -> Self {
+        Self::assert_is_probability(skip_probability);
+        self.skip_probability_per_kind
+            .insert(kind, skip_probability);
+        self
+    }
+    fn assert_is_probability(skip_probability: f64) {
+        assert!(
+            (0.0..=1.0).contains(&skip_probability),
+            "Skip probability must be in 0.0..=1.0, got {skip_probability}"
+        );
+    }
+    pub fn skip_probability_for(&self, kind: FactorSourceKind) -> f64 {
+        self.skip_probability_per_kind
+            .get(&kind)
+            .copied()
+            .unwrap_or(self.skip_probability)
+    }
+    /// Also used to decide if a failing factor source is given up on.
+    fn skips(&self, factor_source: &FactorSource) -> bool {
+        let probability = self.skip_probability_for(factor_source.kind());
+        self.rng.lock().unwrap().gen_bool(probability)
+    }
+}
+
+/// Which prompt a scripted entry answers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptedPrompt {
+    /// The prompt for the factor source with this ID.
+    FactorSource(FactorSourceID),
+
+    /// The prompt for any factor source of this kind.
+    Kind(FactorSourceKind),
+}
+impl ScriptedPrompt {
+    fn matches(&self, factor_source: &FactorSource) -> bool {
+        match self {
+            Self::FactorSource(id) => *id == factor_source.id,
+            Self::Kind(kind) => *kind == factor_source.kind(),
+        }
+    }
+}
+impl From<FactorSourceID> for ScriptedPrompt {
+    fn from(value: FactorSourceID) -> Self {
+        Self::FactorSource(value)
+    }
+}
+impl From<FactorSourceKind> for ScriptedPrompt {
+    fn from(value: FactorSourceKind) -> Self {
+        Self::Kind(value)
+    }
+}
+
+/// A scripted answer, either to a prompt to sign or skip a factor source, or
+/// to a prompt to retry, skip or cancel after it failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScriptedAnswer {
+    SignOrSkip(SigningUserInput),
+    Retry(SigningRetryInput),
+}
+impl From<SigningUserInput> for ScriptedAnswer {
+    fn from(value: SigningUserInput) -> Self {
+        Self::SignOrSkip(value)
+    }
+}
+impl From<SigningRetryInput> for ScriptedAnswer {
+    fn from(value: SigningRetryInput) -> Self {
+        Self::Retry(value)
+    }
+}
+
+#[derive(Debug)]
+struct ScriptEntry {
+    prompt: ScriptedPrompt,
+    answer: ScriptedAnswer,
+    /// If a `ScriptedPrompt::Kind` entry has answered at least once.
+    used: bool,
+}
+
+/// Ordered answers of a `TestSigningUser::Scripted`. An entry for a factor
+/// source is consumed by the prompt it answers, an entry for a kind answers
+/// every subsequent prompt of the same type for a factor source of the kind,
+/// and is consumed by the first prompt it does not answer.
+///
+/// Panics if prompted - including to retry after a failure - for a factor
+/// source not matching the next entry, or - when dropped - if some entries
+/// were never used, failing the test.
+pub struct Script {
+    entries: std::sync::Mutex<std::collections::VecDeque<ScriptEntry>>,
+}
+impl Script {
+    pub fn new(
+        entries: impl IntoIterator<Item = (impl Into<ScriptedPrompt>, impl Into<ScriptedAnswer>)>,
+    ) -> Self {
+        Self {
+            entries: std::sync::Mutex::new(
+                entries
+                    .into_iter()
+                    .map(|(prompt, answer)| ScriptEntry {
+                        prompt: prompt.into(),
+                        answer: answer.into(),
+                        used: false,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The answer of the next entry for `factor_source` which `select`s
+    /// an answer of the type prompted for.
+    fn answer<T>(
+        &self,
+        factor_source: &FactorSource,
+        select: impl Fn(&ScriptedAnswer) -> Option<T>,
+    ) -> T {
+        let mut entries = self.entries.lock().unwrap();
+        loop {
+            let Some(entry) = entries.front_mut() else {
+                panic!(
+                    "Unexpected prompt for factor source {:?}, script is exhausted",
+                    factor_source.id
+                )
+            };
+            let answer = select(&entry.answer).filter(|_| entry.prompt.matches(factor_source));
+            match (answer, entry.prompt) {
+                (Some(answer), ScriptedPrompt::Kind(_)) => {
+                    entry.used = true;
+                    return answer;
+                }
+                (Some(answer), ScriptedPrompt::FactorSource(_)) => {
+                    entries.pop_front();
+                    return answer;
+                }
+                (None, _) if entry.used => {
+                    entries.pop_front();
+                }
+                (None, prompt) => panic!(
+                    "Unexpected prompt for factor source {:?}, script expected {:?}",
+                    factor_source.id, prompt
+                ),
+            }
+        }
+    }
+
+    fn sign_or_skip(&self, factor_source: &FactorSource) -> SigningUserInput {
+        self.answer(factor_source, |answer| match answer {
+            ScriptedAnswer::SignOrSkip(input) => Some(input.clone()),
+            ScriptedAnswer::Retry(_) => None,
+        })
+    }
+
+    fn retry_skip_or_cancel(&self, factor_source: &FactorSource) -> SigningRetryInput {
+        self.answer(factor_source, |answer| match answer {
+            ScriptedAnswer::Retry(input) => Some(input.clone()),
+            ScriptedAnswer::SignOrSkip(_) => None,
+        })
+    }
+}
+impl Drop for Script {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        let entries = self.entries.lock().unwrap();
+        let unused = entries.iter().filter(|e| !e.used).collect::<Vec<_>>();
+        assert!(
+            unused.is_empty(),
+            "Scripted entries never consumed: {:?}",
+            unused
+        );
+    }
+}
+
 #[async_trait::async_trait]
 impl IsSigningUser for TestSigningUser {
     async fn sign_or_skip(
@@ -79,24 +449,91 @@ impl IsSigningUser for TestSigningUser {
         match self {
             TestSigningUser::Prudent => SigningUserInput::Sign,
             TestSigningUser::Lazy(laziness) => (laziness.act)(factor_source, invalid_tx_if_skipped),
-            TestSigningUser::Random => {
-                let mut rng = rand::thread_rng();
-                let num: f64 = rng.gen(); // generates a float between 0 and 1
-                if num > 0.5 {
+            TestSigningUser::Random(randomness) => {
+                if randomness.skips(factor_source) {
                     SigningUserInput::Skip
                 } else {
                     SigningUserInput::Sign
                 }
             }
+            TestSigningUser::Unresponsive => futures::future::pending().await,
+            TestSigningUser::Scripted(script) => script.sign_or_skip(factor_source),
+            TestSigningUser::Replaying(replay) => replay.sign_or_skip(factor_source),
+        }
+    }
+
+    async fn sign_or_skip_kind(
+        &self,
+        kind: FactorSourceKind,
+        invalid_tx_if_skipped_per_factor_source: IndexMap<
+            FactorSource,
+            IndexSet<InvalidTransactionIfSkipped>,
+        >,
+    ) -> SigningKindUserInput {
+        match self {
+            TestSigningUser::Replaying(replay) => replay.sign_or_skip_kind(kind),
+            _ => sign_or_skip_each(self, invalid_tx_if_skipped_per_factor_source).await,
+        }
+    }
+
+    async fn sign_extra_or_skip(&self, factor_source: &FactorSource) -> SigningUserInput {
+        match self {
+            TestSigningUser::Replaying(replay) => replay.sign_extra_or_skip(factor_source),
+            _ => self.sign_or_skip(factor_source, IndexSet::new()).await,
+        }
+    }
+
+    async fn retry_skip_or_cancel(
+        &self,
+        factor_source: &FactorSource,
+        failure: CommonError,
+        _invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        can_retry: bool,
+    ) -> SigningRetryInput {
+        match self {
+            TestSigningUser::Prudent if can_retry => SigningRetryInput::Retry,
+            TestSigningUser::Prudent => SigningRetryInput::Skip,
+            TestSigningUser::Lazy(laziness) => (laziness.on_failure)(factor_source, failure),
+            TestSigningUser::Random(randomness) => {
+                if !can_retry || randomness.skips(factor_source) {
+                    SigningRetryInput::Skip
+                } else {
+                    SigningRetryInput::Retry
+                }
+            }
+            TestSigningUser::Unresponsive => futures::future::pending().await,
+            TestSigningUser::Scripted(script) => script.retry_skip_or_cancel(factor_source),
+            TestSigningUser::Replaying(replay) => replay.retry_skip_or_cancel(factor_source),
         }
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 pub enum SigningUser {
     Test(TestSigningUser),
+
+    /// Any other user, with every prompt and answer recorded, see
+    /// `RecordingSigningUser`.
+    Recording(Box<RecordingSigningUser<SigningUser>>),
+
+    /// A user provided by the host, e.g. a mobile app bridge or a server side
+    /// co-signer.
+    Host(Box<dyn IsSigningUser + Send + Sync>),
 }
 
-unsafe impl Sync for TestSigningUser {}
+impl SigningUser {
+    pub fn host(user: impl IsSigningUser + Send + Sync + 'static) -> Self {
+        Self::Host(Box::new(user))
+    }
+
+    fn user(&self) -> &(dyn IsSigningUser + Send + Sync) {
+        match self {
+            SigningUser::Test(test_user) => test_user,
+            SigningUser::Recording(recording_user) => recording_user.as_ref(),
+            SigningUser::Host(host_user) => host_user.as_ref(),
+        }
+    }
+}
 
 #[async_trait::async_trait]
 impl IsSigningUser for SigningUser {
@@ -105,12 +542,47 @@ impl IsSigningUser for SigningUser {
         factor_source: &FactorSource,
         invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
     ) -> SigningUserInput {
-        match self {
-            SigningUser::Test(test_user) => {
-                test_user
-                    .sign_or_skip(&factor_source, invalid_tx_if_skipped)
-                    .await
-            }
-        }
+        self.user()
+            .sign_or_skip(factor_source, invalid_tx_if_skipped)
+            .await
+    }
+
+    async fn sign_or_skip_in_context(
+        &self,
+        factor_source: &FactorSource,
+        context: SigningPromptContext,
+    ) -> SigningUserInput {
+        self.user()
+            .sign_or_skip_in_context(factor_source, context)
+            .await
+    }
+
+    async fn sign_or_skip_kind(
+        &self,
+        kind: FactorSourceKind,
+        invalid_tx_if_skipped_per_factor_source: IndexMap<
+            FactorSource,
+            IndexSet<InvalidTransactionIfSkipped>,
+        >,
+    ) -> SigningKindUserInput {
+        self.user()
+            .sign_or_skip_kind(kind, invalid_tx_if_skipped_per_factor_source)
+            .await
+    }
+
+    async fn sign_extra_or_skip(&self, factor_source: &FactorSource) -> SigningUserInput {
+        self.user().sign_extra_or_skip(factor_source).await
+    }
+
+    async fn retry_skip_or_cancel(
+        &self,
+        factor_source: &FactorSource,
+        failure: CommonError,
+        invalid_tx_if_skipped: IndexSet<InvalidTransactionIfSkipped>,
+        can_retry: bool,
+    ) -> SigningRetryInput {
+        self.user()
+            .retry_skip_or_cancel(factor_source, failure, invalid_tx_if_skipped, can_retry)
+            .await
     }
 }